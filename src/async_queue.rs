@@ -0,0 +1,529 @@
+// a FIFO for async code: push() never waits, pop() returns a future that resolves once there is
+// an element to hand out. it only uses std::task, so it works with any executor.
+//
+// the elements live in a fifth::List. every pop future that is waiting has a slot of its own,
+// found by a ticket number, and the tickets wait their turn in a second fifth::List. a push wakes
+// the oldest waiting future and no other, so it costs O(1) however many are waiting.
+//
+// a pop future can be dropped at any time, typically when it loses a race against a timeout. its
+// Drop takes its slot out, so cancelled pops don't pile up. if it had already been woken for an
+// element it never took, it passes the wakeup on to the next waiting future, otherwise that
+// element could sit in the queue with every remaining future asleep.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+use crate::fifth::List;
+
+pub struct AsyncQueue<T> {
+    inner: Mutex<Inner<T>>,
+}
+
+struct Inner<T> {
+    queue: List<T>,
+    waiters: HashMap<u64, Slot>,
+    // tickets of the Waiting slots, oldest first. a dropped or finished future leaves its ticket
+    // behind, those are skipped when they come up and counted in `stale`.
+    order: List<u64>,
+    stale: usize,
+    next_ticket: u64,
+}
+
+enum Slot {
+    Waiting(Waker),
+    // woken by a push and not polled since; its ticket has left `order`.
+    Notified,
+}
+
+impl<T> AsyncQueue<T> {
+    pub fn new() -> Self {
+        AsyncQueue {
+            inner: Mutex::new(Inner {
+                queue: List::new(),
+                waiters: HashMap::new(),
+                order: List::new(),
+                stale: 0,
+                next_ticket: 0,
+            }),
+        }
+    }
+
+    // pushes an element to the back and wakes the longest waiting pop().
+    pub fn push(&self, elem: T) {
+        let waker = {
+            let mut inner = self.lock();
+            inner.queue.push(elem);
+            inner.notify_one()
+        };
+
+        // wake outside the lock, a waker may run the task straight away on this thread.
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    // a future that resolves to the front element, waiting for a push if the queue is empty.
+    pub fn pop(&self) -> Pop<'_, T> {
+        Pop {
+            queue: self,
+            ticket: None,
+        }
+    }
+
+    // takes the front element if there is one, without waiting.
+    pub fn try_pop(&self) -> Option<T> {
+        self.lock().queue.pop()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().queue.peek().is_none()
+    }
+
+    // the lock is never held while anything that could panic runs, so poisoning can be ignored.
+    fn lock(&self) -> MutexGuard<'_, Inner<T>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Inner<T> {
+    // marks the oldest waiting future as notified and returns its waker, to be woken once the lock
+    // is released. the stale tickets skipped on the way were each pushed once, so this is O(1)
+    // amortized.
+    fn notify_one(&mut self) -> Option<Waker> {
+        while let Some(ticket) = self.order.pop() {
+            if let Some(slot) = self.waiters.get_mut(&ticket) {
+                if let Slot::Waiting(waker) = mem::replace(slot, Slot::Notified) {
+                    return Some(waker);
+                }
+            }
+            self.stale -= 1;
+        }
+
+        None
+    }
+
+    // takes a future's slot out. a Waiting slot leaves its ticket behind in order; once those
+    // outnumber the live slots, order is rebuilt without them, which keeps it O(1) amortized too.
+    fn remove(&mut self, ticket: u64) -> Option<Slot> {
+        let slot = self.waiters.remove(&ticket);
+
+        if let Some(Slot::Waiting(_)) = slot {
+            self.stale += 1;
+
+            if self.stale > self.waiters.len() {
+                let waiters = &self.waiters;
+                self.order = mem::take(&mut self.order)
+                    .into_iter()
+                    .filter(|ticket| waiters.contains_key(ticket))
+                    .collect();
+                self.stale = 0;
+            }
+        }
+
+        slot
+    }
+}
+
+impl<T> Default for AsyncQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the future returned by AsyncQueue::pop.
+pub struct Pop<'a, T> {
+    queue: &'a AsyncQueue<T>,
+    ticket: Option<u64>, // set once this future has a slot.
+}
+
+impl<T> Future for Pop<'_, T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let queue = self.queue;
+        let mut inner = queue.lock();
+
+        if let Some(elem) = inner.queue.pop() {
+            if let Some(ticket) = self.ticket.take() {
+                inner.remove(ticket);
+            }
+            return Poll::Ready(elem);
+        }
+
+        let ticket = match self.ticket {
+            Some(ticket) => ticket,
+            None => {
+                let ticket = inner.next_ticket;
+                inner.next_ticket += 1;
+                self.ticket = Some(ticket);
+                ticket
+            }
+        };
+
+        match inner.waiters.get_mut(&ticket) {
+            // already waiting: just keep the waker up to date, in case the task moved.
+            Some(Slot::Waiting(waker)) => waker.clone_from(cx.waker()),
+
+            // first poll, or woken but somebody else got the element first: join the back.
+            Some(Slot::Notified) | None => {
+                inner
+                    .waiters
+                    .insert(ticket, Slot::Waiting(cx.waker().clone()));
+                inner.order.push(ticket);
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Pop<'_, T> {
+    fn drop(&mut self) {
+        let ticket = match self.ticket {
+            Some(ticket) => ticket,
+            None => return,
+        };
+
+        let waker = {
+            let mut inner = self.queue.lock();
+            match inner.remove(ticket) {
+                // woken for an element this future will never take, hand that on.
+                Some(Slot::Notified) if inner.queue.peek().is_some() => inner.notify_one(),
+                _ => None,
+            }
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AsyncQueue;
+    use crate::fifth::List;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+    use std::time::Duration;
+
+    type Task = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    // a waker that unparks the thread blocked in block_on.
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    // runs one future to completion on this thread, parking while it is pending.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    // a single threaded executor: tasks sit in a run queue (a fifth::List, naturally) and waking a
+    // task puts it back on the queue. run() panics if every task is stuck waiting.
+    struct Spawned {
+        future: Mutex<Option<Task>>,
+        run_queue: Arc<Mutex<List<Arc<Spawned>>>>,
+    }
+
+    impl Wake for Spawned {
+        fn wake(self: Arc<Self>) {
+            let run_queue = self.run_queue.clone();
+            run_queue.lock().unwrap().push(self);
+        }
+    }
+
+    fn run(tasks: Vec<Task>) {
+        let run_queue = Arc::new(Mutex::new(List::new()));
+        let total = tasks.len();
+
+        for future in tasks {
+            run_queue.lock().unwrap().push(Arc::new(Spawned {
+                future: Mutex::new(Some(future)),
+                run_queue: run_queue.clone(),
+            }));
+        }
+
+        let mut done = 0;
+        loop {
+            let next = run_queue.lock().unwrap().pop();
+            let task = match next {
+                Some(task) => task,
+                None => break,
+            };
+
+            // a task can be queued more than once, it may have finished since.
+            let mut slot = task.future.lock().unwrap();
+            if let Some(mut future) = slot.take() {
+                let waker = Waker::from(task.clone());
+                match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+                    Poll::Ready(()) => done += 1,
+                    Poll::Pending => *slot = Some(future),
+                }
+            }
+        }
+
+        assert_eq!(done, total, "some tasks never finished");
+    }
+
+    // counts how many times it was woken.
+    struct Counter(AtomicUsize);
+
+    impl Wake for Counter {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn push_and_pop() {
+        let queue = AsyncQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.try_pop(), None);
+
+        queue.push(1);
+        queue.push(2);
+        queue.push(3);
+
+        // elements already queued resolve straight away.
+        assert_eq!(block_on(queue.pop()), 1);
+        assert_eq!(queue.try_pop(), Some(2));
+        assert_eq!(block_on(queue.pop()), 3);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn pop_waits_for_push() {
+        let queue = Arc::new(AsyncQueue::new());
+        let got = Arc::new(Mutex::new(Vec::new()));
+
+        // the consumer runs first and has to wait for the producer.
+        let consumer: Task = {
+            let queue = queue.clone();
+            let got = got.clone();
+            Box::pin(async move {
+                for _ in 0..3 {
+                    let elem = queue.pop().await;
+                    got.lock().unwrap().push(elem);
+                }
+            })
+        };
+
+        let producer: Task = {
+            let queue = queue.clone();
+            Box::pin(async move {
+                for elem in 1..=3 {
+                    queue.push(elem);
+                }
+            })
+        };
+
+        run(vec![consumer, producer]);
+        assert_eq!(*got.lock().unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn many_waiters() {
+        let queue = Arc::new(AsyncQueue::new());
+        let got = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks: Vec<Task> = Vec::new();
+
+        // four consumers waiting on one producer, each push has to pick one of them to wake.
+        for _ in 0..4 {
+            let queue = queue.clone();
+            let got = got.clone();
+            tasks.push(Box::pin(async move {
+                for _ in 0..5 {
+                    let elem = queue.pop().await;
+                    got.lock().unwrap().push(elem);
+                }
+            }));
+        }
+
+        tasks.push(Box::pin({
+            let queue = queue.clone();
+            async move {
+                for elem in 0..20 {
+                    queue.push(elem);
+                }
+            }
+        }));
+
+        run(tasks);
+
+        let mut got = got.lock().unwrap().clone();
+        got.sort();
+        assert_eq!(got, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn push_from_another_thread() {
+        let queue = Arc::new(AsyncQueue::new());
+
+        let handle = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                queue.push(String::from("hello"));
+            })
+        };
+
+        assert_eq!(block_on(queue.pop()), "hello");
+        handle.join().unwrap();
+    }
+
+    // polls a future once with a waker that counts its wakeups.
+    fn poll_with<F: Future + Unpin>(future: &mut F, counter: &Arc<Counter>) -> Poll<F::Output> {
+        let waker = Waker::from(counter.clone());
+        Pin::new(future).poll(&mut Context::from_waker(&waker))
+    }
+
+    fn counter() -> Arc<Counter> {
+        Arc::new(Counter(AtomicUsize::new(0)))
+    }
+
+    fn woken(counter: &Arc<Counter>) -> usize {
+        counter.0.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn dropped_waiter_loses_nothing() {
+        let queue = AsyncQueue::new();
+        let (first, second) = (counter(), counter());
+
+        let mut abandoned = Box::pin(queue.pop());
+        let mut waiting = Box::pin(queue.pop());
+        assert_eq!(poll_with(&mut abandoned, &first), Poll::Pending);
+        assert_eq!(poll_with(&mut waiting, &second), Poll::Pending);
+
+        // polling again without a push must not register a second time.
+        assert_eq!(poll_with(&mut waiting, &second), Poll::Pending);
+        assert_eq!(queue.lock().waiters.len(), 2);
+
+        // the first waiter gives up before the push, so the push goes to the second one.
+        drop(abandoned);
+        assert_eq!(queue.lock().waiters.len(), 1);
+        queue.push(7);
+        assert_eq!(woken(&second), 1);
+        assert_eq!(poll_with(&mut waiting, &second), Poll::Ready(7));
+        assert_eq!(woken(&first), 0);
+        assert_eq!(queue.lock().waiters.len(), 0);
+    }
+
+    #[test]
+    fn push_wakes_one() {
+        let queue = AsyncQueue::new();
+        let counters: Vec<_> = (0..4).map(|_| counter()).collect();
+        let mut pops: Vec<_> = counters.iter().map(|_| Box::pin(queue.pop())).collect();
+
+        for (pop, counter) in pops.iter_mut().zip(&counters) {
+            assert_eq!(poll_with(pop, counter), Poll::Pending);
+        }
+
+        // pushes go to the waiters in the order they started waiting.
+        queue.push(1);
+        let woken_now: Vec<_> = counters.iter().map(woken).collect();
+        assert_eq!(woken_now, vec![1, 0, 0, 0]);
+
+        queue.push(2);
+        let woken_now: Vec<_> = counters.iter().map(woken).collect();
+        assert_eq!(woken_now, vec![1, 1, 0, 0]);
+
+        assert_eq!(poll_with(&mut pops[0], &counters[0]), Poll::Ready(1));
+        assert_eq!(poll_with(&mut pops[1], &counters[1]), Poll::Ready(2));
+    }
+
+    #[test]
+    fn woken_then_dropped_passes_it_on() {
+        let queue = AsyncQueue::new();
+        let (first, second) = (counter(), counter());
+
+        let mut cancelled = Box::pin(queue.pop());
+        let mut waiting = Box::pin(queue.pop());
+        assert_eq!(poll_with(&mut cancelled, &first), Poll::Pending);
+        assert_eq!(poll_with(&mut waiting, &second), Poll::Pending);
+
+        // the push wakes the first waiter, which is dropped before it gets to run.
+        queue.push(7);
+        assert_eq!((woken(&first), woken(&second)), (1, 0));
+        drop(cancelled);
+
+        // so the wakeup moves on to the second waiter.
+        assert_eq!(woken(&second), 1);
+        assert_eq!(poll_with(&mut waiting, &second), Poll::Ready(7));
+    }
+
+    #[test]
+    fn woken_without_an_element_waits_again() {
+        let queue = AsyncQueue::new();
+        let (first, second) = (counter(), counter());
+
+        let mut pop = Box::pin(queue.pop());
+        assert_eq!(poll_with(&mut pop, &first), Poll::Pending);
+
+        // somebody else takes the element between the wakeup and the poll.
+        queue.push(1);
+        assert_eq!(queue.try_pop(), Some(1));
+        assert_eq!(poll_with(&mut pop, &second), Poll::Pending);
+
+        // the future is back in line, with the waker it was last polled with.
+        queue.push(2);
+        assert_eq!((woken(&first), woken(&second)), (1, 1));
+        assert_eq!(poll_with(&mut pop, &second), Poll::Ready(2));
+    }
+
+    #[test]
+    fn cancelled_pops_leave_nothing_behind() {
+        let queue = AsyncQueue::<i32>::new();
+        let waker = counter();
+        let rounds = if cfg!(miri) { 100 } else { 10_000 };
+
+        // a few futures that keep waiting, and lots that give up, as if they had timed out.
+        let mut kept: Vec<_> = (0..3).map(|_| Box::pin(queue.pop())).collect();
+        for pop in &mut kept {
+            assert_eq!(poll_with(pop, &waker), Poll::Pending);
+        }
+
+        for _ in 0..rounds {
+            let mut pop = Box::pin(queue.pop());
+            assert_eq!(poll_with(&mut pop, &waker), Poll::Pending);
+        }
+
+        {
+            let inner = queue.lock();
+            assert_eq!(inner.waiters.len(), 3);
+            assert!(inner.order.iter().count() <= 2 * 3);
+        }
+
+        drop(kept);
+        let inner = queue.lock();
+        assert_eq!(inner.waiters.len(), 0);
+        assert_eq!(inner.order.iter().count(), 0);
+        drop(inner);
+
+        // nobody is waiting any more, so nothing gets woken.
+        queue.push(1);
+        assert_eq!(woken(&waker), 0);
+    }
+}
@@ -0,0 +1,474 @@
+// a blocking channel whose buffer is a fifth::List behind a Mutex, with Condvars to sleep on.
+//
+// unlike std::sync::mpsc the receiver can look at everything that is queued with inspect(), and
+// take all of it at once with drain(), which swaps the whole list out in O(1).
+//
+// there can be any number of Senders but only one Receiver. bounded channels make send() wait
+// while the buffer is full, which pushes back on producers that are faster than the receiver.
+
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::fifth::List;
+
+// a channel that holds at most `capacity` elements; send() blocks while it is full.
+//
+// panics if capacity is 0, there is no rendezvous mode.
+pub fn bounded<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(
+        capacity > 0,
+        "a bounded channel needs room for at least one element"
+    );
+    channel(Some(capacity))
+}
+
+// a channel with no limit; send() never blocks.
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    channel(None)
+}
+
+fn channel<T>(capacity: Option<usize>) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: List::new(),
+            len: 0,
+            senders: 1,
+            receiver: true,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+        capacity,
+    });
+
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared },
+    )
+}
+
+struct State<T> {
+    queue: List<T>,
+    len: usize, // fifth::List doesn't count, the capacity check needs it.
+    senders: usize,
+    receiver: bool, // false once the Receiver is dropped.
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar, // receivers wait on this.
+    not_full: Condvar,  // senders to a full bounded channel wait on this.
+    capacity: Option<usize>,
+}
+
+impl<T> Shared<T> {
+    // the state is never left half updated, so a poisoned lock is still safe to use. the only user
+    // code that runs while it is locked is the closure passed to inspect(), and that only gets
+    // &List, so even if it panics it can't have changed anything.
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_full(&self, state: &State<T>) -> bool {
+        self.capacity.is_some_and(|capacity| state.len >= capacity)
+    }
+}
+
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    // queues elem, waiting for room first if the channel is bounded and full.
+    //
+    // fails, handing elem back, if the Receiver is gone.
+    pub fn send(&self, elem: T) -> Result<(), SendError<T>> {
+        let mut state = self.shared.lock();
+
+        while state.receiver && self.shared.is_full(&state) {
+            state = self
+                .shared
+                .not_full
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        if !state.receiver {
+            return Err(SendError(elem));
+        }
+
+        state.queue.push(elem);
+        state.len += 1;
+        drop(state);
+
+        self.shared.not_empty.notify_one();
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+
+        Sender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+
+        // the last sender going wakes the receiver so it can see the disconnect.
+        if state.senders == 0 {
+            drop(state);
+            self.shared.not_empty.notify_all();
+        }
+    }
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Receiver<T> {
+    // waits for the next element. fails once the channel is empty and every Sender is gone.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let mut state = self.shared.lock();
+
+        loop {
+            if let Some(elem) = self.pop(&mut state) {
+                return Ok(elem);
+            }
+
+            if state.senders == 0 {
+                return Err(RecvError);
+            }
+
+            state = self
+                .shared
+                .not_empty
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    // takes the next element if there is one, without waiting.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.shared.lock();
+
+        match self.pop(&mut state) {
+            Some(elem) => Ok(elem),
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    // like recv() but gives up after timeout.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.lock();
+
+        loop {
+            if let Some(elem) = self.pop(&mut state) {
+                return Ok(elem);
+            }
+
+            if state.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+
+            // wakeups can be spurious, so wait for whatever is left of the timeout each time.
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RecvTimeoutError::Timeout);
+            }
+
+            state = self
+                .shared
+                .not_empty
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    // takes everything that is queued right now as one list, in O(1).
+    pub fn drain(&self) -> List<T> {
+        let mut state = self.shared.lock();
+        let len = mem::replace(&mut state.len, 0);
+        let queue = mem::take(&mut state.queue);
+        drop(state);
+
+        // the whole buffer just freed up.
+        if len > 0 {
+            self.shared.not_full.notify_all();
+        }
+
+        queue
+    }
+
+    // calls f with the queued elements, front first. senders are blocked until f returns.
+    //
+    // f runs with the channel locked, and the lock isn't reentrant: calling any method of this
+    // Receiver (even len()) or sending on the same channel from inside f deadlocks. collect what
+    // is needed from the list and act on it after inspect returns.
+    pub fn inspect<R, F: FnOnce(&List<T>) -> R>(&self, f: F) -> R {
+        f(&self.shared.lock().queue)
+    }
+
+    // the number of queued elements.
+    pub fn len(&self) -> usize {
+        self.shared.lock().len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn pop(&self, state: &mut State<T>) -> Option<T> {
+        let elem = state.queue.pop()?;
+        state.len -= 1;
+
+        // one slot opened up, so one waiting sender can go.
+        if self.shared.capacity.is_some() {
+            self.shared.not_full.notify_one();
+        }
+
+        Some(elem)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.lock().receiver = false;
+
+        // senders blocked on a full channel would otherwise wait forever.
+        self.shared.not_full.notify_all();
+    }
+}
+
+// the Receiver was dropped; the element that couldn't be sent is handed back.
+#[derive(PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+// every Sender was dropped and the channel is empty.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RecvError;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected,
+}
+
+// Debug doesn't need T: Debug, like std's SendError, so unwrap() works for any T.
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SendError { .. }")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("sending on a channel with no receiver")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("receiving on an empty channel with no senders")
+    }
+}
+
+impl Error for RecvError {}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("receiving on an empty channel"),
+            TryRecvError::Disconnected => {
+                f.write_str("receiving on an empty channel with no senders")
+            }
+        }
+    }
+}
+
+impl Error for TryRecvError {}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => f.write_str("timed out waiting on the channel"),
+            RecvTimeoutError::Disconnected => {
+                f.write_str("receiving on an empty channel with no senders")
+            }
+        }
+    }
+}
+
+impl Error for RecvTimeoutError {}
+
+#[cfg(test)]
+mod test {
+    use super::{bounded, unbounded, RecvError, RecvTimeoutError, SendError, TryRecvError};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn send_and_recv() {
+        let (tx, rx) = unbounded();
+
+        // check empty channel behaves right.
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        tx.clone().send(3).unwrap();
+        assert_eq!(rx.len(), 3);
+
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Ok(2));
+        assert_eq!(rx.recv_timeout(Duration::from_millis(10)), Ok(3));
+
+        // check exhaustion.
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Timeout)
+        );
+    }
+
+    #[test]
+    fn disconnect() {
+        let (tx, rx) = unbounded();
+        let tx2 = tx.clone();
+
+        tx.send(1).unwrap();
+        drop(tx);
+        tx2.send(2).unwrap();
+        drop(tx2);
+
+        // what was sent before the disconnect is still delivered.
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Ok(2));
+        assert_eq!(rx.recv(), Err(RecvError));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+
+        // the other way round, sending hands the element back.
+        let (tx, rx) = unbounded();
+        drop(rx);
+        assert_eq!(
+            tx.send(String::from("lost")),
+            Err(SendError(String::from("lost")))
+        );
+    }
+
+    #[test]
+    fn blocked_recv_wakes_on_disconnect() {
+        let (tx, rx) = unbounded::<i32>();
+
+        let handle = thread::spawn(move || rx.recv());
+        thread::sleep(Duration::from_millis(20));
+        drop(tx);
+
+        assert_eq!(handle.join().unwrap(), Err(RecvError));
+    }
+
+    #[test]
+    fn backpressure() {
+        let (tx, rx) = bounded(2);
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+
+        // the third send has to wait for room.
+        let handle = thread::spawn(move || {
+            tx.send(3).unwrap();
+            tx
+        });
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(rx.len(), 2);
+
+        assert_eq!(rx.recv(), Ok(1));
+        let tx = handle.join().unwrap();
+        assert_eq!(
+            rx.inspect(|queue| queue.iter().copied().collect::<Vec<_>>()),
+            vec![2, 3]
+        );
+
+        // a sender blocked on a full channel is woken when the receiver goes.
+        let handle = thread::spawn(move || tx.send(4));
+        thread::sleep(Duration::from_millis(20));
+        drop(rx);
+        assert_eq!(handle.join().unwrap(), Err(SendError(4)));
+    }
+
+    #[test]
+    fn drain() {
+        let (tx, rx) = bounded(3);
+        for elem in 0..3 {
+            tx.send(elem).unwrap();
+        }
+
+        let handle = thread::spawn(move || {
+            for elem in 3..6 {
+                tx.send(elem).unwrap();
+            }
+        });
+
+        // draining frees up the whole buffer at once.
+        let mut seen = Vec::new();
+        while seen.len() < 6 {
+            seen.extend(rx.drain());
+        }
+        handle.join().unwrap();
+
+        assert_eq!(seen, vec![0, 1, 2, 3, 4, 5]);
+        assert!(rx.is_empty());
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn many_senders() {
+        const SENDERS: usize = 8;
+        const SENDS: usize = 1000;
+
+        let (tx, rx) = bounded(16);
+
+        for s in 0..SENDERS {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for seq in 0..SENDS {
+                    tx.send((s, seq)).unwrap();
+                }
+            });
+        }
+        drop(tx);
+
+        // each sender's elements arrive in order, and recv ends once they have all finished.
+        let mut next = [0; SENDERS];
+        while let Ok((s, seq)) = rx.recv() {
+            assert_eq!(seq, next[s]);
+            next[s] += 1;
+        }
+        assert_eq!(next, [SENDS; SENDERS]);
+    }
+}
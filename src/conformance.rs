@@ -0,0 +1,453 @@
+// reusable checks for anything that implements the traits in `crate::traits`.
+//
+// every function here panics through assert! when the list under test misbehaves, so it can be
+// called straight from a #[test] in this crate or in any other crate with its own list type:
+//
+//     #[test]
+//     fn conformance() {
+//         lists::conformance::stack::<MyList<i32>>();
+//     }
+//
+// the checks only use i32 elements and build their lists with Default, apart from std_traits
+// which collects them.
+
+use std::cmp;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::{self, FromIterator};
+
+use crate::traits::{Deque, PersistentStack, Queue, Stack};
+
+// runs every Stack check.
+pub fn stack<S: Stack<i32> + Default>() {
+    stack_push_and_pop::<S>();
+    stack_peek::<S>();
+    stack_interleaved::<S>();
+}
+
+// runs every Queue check.
+pub fn queue<Q: Queue<i32> + Default>() {
+    queue_push_and_pop::<Q>();
+    queue_peek::<Q>();
+    queue_interleaved::<Q>();
+}
+
+// runs every Queue check and then the Deque ones.
+pub fn deque<D: Deque<i32> + Default>() {
+    queue::<D>();
+    deque_push_and_pop::<D>();
+    deque_interleaved::<D>();
+}
+
+// runs every PersistentStack check.
+pub fn persistent_stack<S: PersistentStack<i32> + Default>() {
+    persistent_stack_heads_and_tails::<S>();
+    persistent_stack_versions::<S>();
+}
+
+pub fn stack_push_and_pop<S: Stack<i32> + Default>() {
+    let mut stack = S::default();
+
+    // check empty stack behaves right.
+    assert!(stack.is_empty());
+    assert_eq!(stack.pop(), None);
+
+    // populate stack.
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+    assert!(!stack.is_empty());
+
+    // check normal removal.
+    assert_eq!(stack.pop(), Some(3));
+    assert_eq!(stack.pop(), Some(2));
+
+    // push some more to make sure nothing is corrupted.
+    stack.push(4);
+    stack.push(5);
+
+    // check normal removal.
+    assert_eq!(stack.pop(), Some(5));
+    assert_eq!(stack.pop(), Some(4));
+
+    // check exhaustion.
+    assert_eq!(stack.pop(), Some(1));
+    assert_eq!(stack.pop(), None);
+    assert_eq!(stack.pop(), None);
+    assert!(stack.is_empty());
+
+    // check the stack can be reused after being emptied.
+    stack.push(6);
+    assert_eq!(stack.pop(), Some(6));
+    assert_eq!(stack.pop(), None);
+}
+
+pub fn stack_peek<S: Stack<i32> + Default>() {
+    let mut stack = S::default();
+    assert!(stack.peek().is_none());
+
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+
+    // peeking must not remove anything.
+    assert_eq!(stack.peek().as_deref(), Some(&3));
+    assert_eq!(stack.peek().as_deref(), Some(&3));
+    assert_eq!(stack.pop(), Some(3));
+    assert_eq!(stack.peek().as_deref(), Some(&2));
+
+    assert_eq!(stack.pop(), Some(2));
+    assert_eq!(stack.pop(), Some(1));
+    assert!(stack.peek().is_none());
+}
+
+// consuming a stack must hand its elements out in pop order.
+pub fn stack_into_iter<S: Stack<i32> + Default + IntoIterator<Item = i32>>() {
+    let mut stack = S::default();
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+
+    let mut iter = stack.into_iter();
+    assert_eq!(iter.next(), Some(3));
+    assert_eq!(iter.next(), Some(2));
+    assert_eq!(iter.next(), Some(1));
+    assert_eq!(iter.next(), None);
+
+    // an empty stack gives an empty iterator.
+    assert_eq!(S::default().into_iter().next(), None);
+}
+
+// a fixed mix of pushes and pops, checked against a Vec after every step.
+pub fn stack_interleaved<S: Stack<i32> + Default>() {
+    let mut stack = S::default();
+    let mut model = Vec::new();
+
+    for (elem, op) in interleaving().enumerate() {
+        match op {
+            Op::Push => {
+                stack.push(elem as i32);
+                model.push(elem as i32);
+            }
+            Op::Pop => assert_eq!(stack.pop(), model.pop()),
+        }
+
+        assert_eq!(stack.peek().as_deref(), model.last());
+        assert_eq!(stack.is_empty(), model.is_empty());
+    }
+
+    // drain what is left.
+    while let Some(elem) = model.pop() {
+        assert_eq!(stack.pop(), Some(elem));
+    }
+    assert_eq!(stack.pop(), None);
+}
+
+pub fn queue_push_and_pop<Q: Queue<i32> + Default>() {
+    let mut queue = Q::default();
+
+    // check empty queue behaves right.
+    assert!(queue.is_empty());
+    assert_eq!(queue.pop_front(), None);
+
+    // populate queue.
+    queue.push_back(1);
+    queue.push_back(2);
+    queue.push_back(3);
+    assert!(!queue.is_empty());
+
+    // check normal removal.
+    assert_eq!(queue.pop_front(), Some(1));
+    assert_eq!(queue.pop_front(), Some(2));
+
+    // push some more to make sure nothing is corrupted.
+    queue.push_back(4);
+    queue.push_back(5);
+
+    // check normal removal.
+    assert_eq!(queue.pop_front(), Some(3));
+    assert_eq!(queue.pop_front(), Some(4));
+
+    // check exhaustion.
+    assert_eq!(queue.pop_front(), Some(5));
+    assert_eq!(queue.pop_front(), None);
+    assert_eq!(queue.pop_front(), None);
+    assert!(queue.is_empty());
+
+    // check the queue can be reused after being emptied, this is where a stale tail shows up.
+    queue.push_back(6);
+    queue.push_back(7);
+    assert_eq!(queue.pop_front(), Some(6));
+    assert_eq!(queue.pop_front(), Some(7));
+    assert_eq!(queue.pop_front(), None);
+}
+
+pub fn queue_peek<Q: Queue<i32> + Default>() {
+    let mut queue = Q::default();
+    assert!(queue.peek_front().is_none());
+
+    queue.push_back(1);
+    queue.push_back(2);
+    queue.push_back(3);
+
+    // peeking must not remove anything.
+    assert_eq!(queue.peek_front().as_deref(), Some(&1));
+    assert_eq!(queue.peek_front().as_deref(), Some(&1));
+    assert_eq!(queue.pop_front(), Some(1));
+    assert_eq!(queue.peek_front().as_deref(), Some(&2));
+
+    assert_eq!(queue.pop_front(), Some(2));
+    assert_eq!(queue.pop_front(), Some(3));
+    assert!(queue.peek_front().is_none());
+}
+
+// consuming a queue must hand its elements out in pop_front order.
+pub fn queue_into_iter<Q: Queue<i32> + Default + IntoIterator<Item = i32>>() {
+    let mut queue = Q::default();
+    queue.push_back(1);
+    queue.push_back(2);
+    queue.push_back(3);
+
+    let mut iter = queue.into_iter();
+    assert_eq!(iter.next(), Some(1));
+    assert_eq!(iter.next(), Some(2));
+    assert_eq!(iter.next(), Some(3));
+    assert_eq!(iter.next(), None);
+
+    // an empty queue gives an empty iterator.
+    assert_eq!(Q::default().into_iter().next(), None);
+}
+
+// a fixed mix of pushes and pops, checked against a VecDeque after every step.
+pub fn queue_interleaved<Q: Queue<i32> + Default>() {
+    let mut queue = Q::default();
+    let mut model = VecDeque::new();
+
+    for (elem, op) in interleaving().enumerate() {
+        match op {
+            Op::Push => {
+                queue.push_back(elem as i32);
+                model.push_back(elem as i32);
+            }
+            Op::Pop => assert_eq!(queue.pop_front(), model.pop_front()),
+        }
+
+        assert_eq!(queue.peek_front().as_deref(), model.front());
+        assert_eq!(queue.is_empty(), model.is_empty());
+    }
+
+    // drain what is left.
+    while let Some(elem) = model.pop_front() {
+        assert_eq!(queue.pop_front(), Some(elem));
+    }
+    assert_eq!(queue.pop_front(), None);
+}
+
+pub fn deque_push_and_pop<D: Deque<i32> + Default>() {
+    let mut deque = D::default();
+
+    // check empty deque behaves right from both ends.
+    assert_eq!(deque.pop_front(), None);
+    assert_eq!(deque.pop_back(), None);
+    assert!(deque.peek_back().is_none());
+
+    deque.push_front(2);
+    deque.push_front(3);
+    deque.push_back(1);
+    assert_eq!(deque.peek_front().as_deref(), Some(&3));
+    assert_eq!(deque.peek_back().as_deref(), Some(&1));
+
+    // check normal removal.
+    assert_eq!(deque.pop_front(), Some(3));
+    assert_eq!(deque.pop_front(), Some(2));
+
+    // push more to make sure nothing is corrupted.
+    deque.push_front(4);
+    deque.push_front(5);
+
+    // check normal removal from the back.
+    assert_eq!(deque.pop_back(), Some(1));
+    assert_eq!(deque.pop_back(), Some(4));
+
+    // a single element is both the front and the back.
+    assert_eq!(deque.peek_front().as_deref(), Some(&5));
+    assert_eq!(deque.peek_back().as_deref(), Some(&5));
+
+    // check exhaustion.
+    assert_eq!(deque.pop_back(), Some(5));
+    assert_eq!(deque.pop_back(), None);
+    assert_eq!(deque.pop_front(), None);
+
+    // check the deque can be reused after being emptied.
+    deque.push_back(6);
+    deque.push_front(7);
+    assert_eq!(deque.pop_back(), Some(6));
+    assert_eq!(deque.pop_back(), Some(7));
+    assert!(deque.is_empty());
+}
+
+// a fixed mix of operations on both ends, checked against a VecDeque after every step.
+pub fn deque_interleaved<D: Deque<i32> + Default>() {
+    let mut deque = D::default();
+    let mut model = VecDeque::new();
+
+    for (elem, op) in interleaving().enumerate() {
+        // alternate which end is used so every pairing of push and pop ends comes up.
+        let front = elem % 4 < 2;
+
+        match (op, front) {
+            (Op::Push, true) => {
+                deque.push_front(elem as i32);
+                model.push_front(elem as i32);
+            }
+            (Op::Push, false) => {
+                deque.push_back(elem as i32);
+                model.push_back(elem as i32);
+            }
+            (Op::Pop, true) => assert_eq!(deque.pop_front(), model.pop_front()),
+            (Op::Pop, false) => assert_eq!(deque.pop_back(), model.pop_back()),
+        }
+
+        assert_eq!(deque.peek_front().as_deref(), model.front());
+        assert_eq!(deque.peek_back().as_deref(), model.back());
+    }
+
+    while let Some(elem) = model.pop_back() {
+        assert_eq!(deque.pop_back(), Some(elem));
+    }
+    assert_eq!(deque.pop_front(), None);
+}
+
+pub fn persistent_stack_heads_and_tails<S: PersistentStack<i32> + Default>() {
+    let stack = S::default();
+    assert_eq!(stack.head(), None);
+    assert!(stack.is_empty());
+
+    // returns a new stack instead of mutating.
+    let stack = stack.push(1).push(2).push(3);
+    assert_eq!(stack.head(), Some(&3));
+    assert!(!stack.is_empty());
+
+    let stack = stack.tail();
+    assert_eq!(stack.head(), Some(&2));
+
+    let stack = stack.tail();
+    assert_eq!(stack.head(), Some(&1));
+
+    // check exhaustion.
+    let stack = stack.tail();
+    assert_eq!(stack.head(), None);
+
+    // the tail of an empty stack is still empty.
+    let stack = stack.tail();
+    assert_eq!(stack.head(), None);
+
+    // check the stack can be reused after being emptied.
+    let stack = stack.push(4);
+    assert_eq!(stack.head(), Some(&4));
+    assert_eq!(stack.tail().head(), None);
+}
+
+// older versions must never see changes made through newer ones.
+pub fn persistent_stack_versions<S: PersistentStack<i32> + Default>() {
+    let base = S::default().push(1).push(2);
+    let left = base.push(3);
+    let right = base.push(4);
+    let shorter = base.tail();
+
+    assert_eq!(base.head(), Some(&2));
+    assert_eq!(left.head(), Some(&3));
+    assert_eq!(right.head(), Some(&4));
+    assert_eq!(shorter.head(), Some(&1));
+
+    // both branches still lead back to the same base.
+    assert_eq!(left.tail().head(), Some(&2));
+    assert_eq!(right.tail().tail().head(), Some(&1));
+
+    // dropping the base must not affect versions built from it.
+    drop(base);
+    assert_eq!(left.tail().tail().head(), Some(&1));
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Push,
+    Pop,
+}
+
+// the std traits every list in this crate implements, checked on short lists and on one long
+// enough that recursing once per node would overflow the stack.
+//
+// the lists all implement them the same way:
+//
+// - they walk the list with a loop or an iterator, never by recursing into `next`, for the same
+//   reason Drop loops.
+// - collecting keeps the order of the iterator, and extend adds to the end, so a list collected
+//   from an iterator iterates in the same order.
+// - Hash writes the elements and then the length. without the length, [[1, 2], [3]] and
+//   [[1], [2, 3]] would feed the hasher the same numbers.
+pub fn std_traits<L>()
+where
+    L: FromIterator<i32> + Extend<i32> + Clone + fmt::Debug + Eq + Ord + Hash,
+{
+    let mut list: L = vec![1, 2, 3].into_iter().collect();
+    assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+    assert_eq!(format!("{:?}", L::from_iter(None)), "[]");
+
+    // check extend appends after the existing elements.
+    list.extend(vec![4, 5]);
+    assert_eq!(format!("{:?}", list), "[1, 2, 3, 4, 5]");
+
+    let clone = list.clone();
+    assert_eq!(clone, list);
+    assert_eq!(hash(&clone), hash(&list));
+
+    // comparing a list with itself must work too, fourth::List borrows both sides at once.
+    #[allow(clippy::eq_op)]
+    {
+        assert_eq!(list, list);
+    }
+
+    let shorter: L = (2..=5).collect();
+    assert_ne!(clone, shorter);
+    assert!(clone < shorter);
+    assert!(L::from_iter(None) < clone);
+
+    // check lists can be used as set keys, and nesting doesn't collide.
+    let mut set = HashSet::new();
+    assert!(set.insert(clone.clone()));
+    assert!(!set.insert(clone));
+    assert!(set.insert(shorter));
+
+    let split = |at: i32| -> [L; 2] { [(1..at).collect(), (at..=3).collect()] };
+    assert_ne!(hash(&split(2)), hash(&split(3)));
+
+    // the long list is built twice rather than cloned, some lists share nodes when cloning. miri
+    // is thousands of times slower, so under it the list is only long enough to cover the loops.
+    let len = if cfg!(miri) { 1_000 } else { 1_000_000 };
+    let long: L = (0..len).collect();
+    let copy: L = (0..len).collect();
+    assert_eq!(copy, long);
+    assert_eq!(copy.cmp(&long), cmp::Ordering::Equal);
+    assert_eq!(hash(&copy), hash(&long));
+    assert_eq!(format!("{:?}", long.clone()), format!("{:?}", copy));
+    assert!(format!("{:?}", long).ends_with(&format!("{}]", len - 1)));
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+// the operations used by the interleaved checks: runs of pushes and pops that grow and shrink the
+// list several times, including popping past empty.
+const RUNS: [(usize, usize); 7] = [(3, 1), (1, 3), (5, 2), (2, 6), (4, 4), (7, 1), (1, 9)];
+
+fn interleaving() -> impl Iterator<Item = Op> {
+    RUNS.iter().flat_map(|&(pushes, pops)| {
+        iter::repeat_n(Op::Push, pushes).chain(iter::repeat_n(Op::Pop, pops))
+    })
+}
@@ -0,0 +1,281 @@
+// differential testing: the same random sequence of operations is applied to a list and to a
+// std::collections reference model, and the two are compared after every single step.
+//
+// the hand-written tests in each module only ever hold three to five elements. bugs like a stale
+// tail pointer after a queue is drained only show up with longer, messier histories, which is what
+// this drives. the random numbers come from a small built-in generator seeded by the caller, so a
+// failing run is reproduced by running it again with the same seed.
+//
+//     #[test]
+//     fn differential() {
+//         for seed in 0..16 {
+//             lists::differential::queue::<MyQueue<i32>>(seed, 2_000);
+//         }
+//     }
+
+use std::collections::VecDeque;
+
+use crate::traits::{Deque, PersistentStack, Queue, Stack};
+
+// splitmix64, small and good enough to shuffle test operations; not for anything else.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // a number in 0..bound, bound must not be 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+// picks the next operation out of 100: mostly pushes so the list grows, with the odd full drain
+// so that emptying and refilling is covered too.
+fn roll(rng: &mut Rng) -> Roll {
+    match rng.below(100) {
+        0..=54 => Roll::Push,
+        55..=97 => Roll::Pop,
+        _ => Roll::Drain,
+    }
+}
+
+enum Roll {
+    Push,
+    Pop,
+    Drain,
+}
+
+// runs `steps` random operations against a Stack and a Vec.
+pub fn stack<S: Stack<i32> + Default>(seed: u64, steps: usize) {
+    let mut rng = Rng::new(seed);
+    let mut stack = S::default();
+    let mut model = Vec::new();
+
+    for step in 0..steps {
+        match roll(&mut rng) {
+            Roll::Push => {
+                let elem = rng.next_u64() as i32;
+                stack.push(elem);
+                model.push(elem);
+            }
+
+            Roll::Pop => assert_eq!(stack.pop(), model.pop(), "seed {} step {}", seed, step),
+
+            Roll::Drain => {
+                while let Some(elem) = model.pop() {
+                    assert_eq!(stack.pop(), Some(elem), "seed {} step {}", seed, step);
+                }
+                assert_eq!(stack.pop(), None, "seed {} step {}", seed, step);
+            }
+        }
+
+        assert_eq!(
+            stack.peek().as_deref(),
+            model.last(),
+            "seed {} step {}",
+            seed,
+            step
+        );
+        assert_eq!(stack.is_empty(), model.is_empty());
+    }
+
+    while let Some(elem) = model.pop() {
+        assert_eq!(stack.pop(), Some(elem), "seed {} final drain", seed);
+    }
+    assert_eq!(stack.pop(), None);
+}
+
+// runs `steps` random operations against a Queue and a VecDeque.
+pub fn queue<Q: Queue<i32> + Default>(seed: u64, steps: usize) {
+    let mut rng = Rng::new(seed);
+    let mut queue = Q::default();
+    let mut model = VecDeque::new();
+
+    for step in 0..steps {
+        match roll(&mut rng) {
+            Roll::Push => {
+                let elem = rng.next_u64() as i32;
+                queue.push_back(elem);
+                model.push_back(elem);
+            }
+
+            Roll::Pop => assert_eq!(
+                queue.pop_front(),
+                model.pop_front(),
+                "seed {} step {}",
+                seed,
+                step
+            ),
+
+            Roll::Drain => {
+                while let Some(elem) = model.pop_front() {
+                    assert_eq!(queue.pop_front(), Some(elem), "seed {} step {}", seed, step);
+                }
+                assert_eq!(queue.pop_front(), None, "seed {} step {}", seed, step);
+            }
+        }
+
+        assert_eq!(
+            queue.peek_front().as_deref(),
+            model.front(),
+            "seed {} step {}",
+            seed,
+            step
+        );
+        assert_eq!(queue.is_empty(), model.is_empty());
+    }
+
+    while let Some(elem) = model.pop_front() {
+        assert_eq!(queue.pop_front(), Some(elem), "seed {} final drain", seed);
+    }
+    assert_eq!(queue.pop_front(), None);
+}
+
+// runs `steps` random operations on both ends of a Deque and a VecDeque.
+pub fn deque<D: Deque<i32> + Default>(seed: u64, steps: usize) {
+    let mut rng = Rng::new(seed);
+    let mut deque = D::default();
+    let mut model = VecDeque::new();
+
+    for step in 0..steps {
+        let front = rng.below(2) == 0;
+
+        match (roll(&mut rng), front) {
+            (Roll::Push, true) => {
+                let elem = rng.next_u64() as i32;
+                deque.push_front(elem);
+                model.push_front(elem);
+            }
+
+            (Roll::Push, false) => {
+                let elem = rng.next_u64() as i32;
+                deque.push_back(elem);
+                model.push_back(elem);
+            }
+
+            (Roll::Pop, true) => assert_eq!(
+                deque.pop_front(),
+                model.pop_front(),
+                "seed {} step {}",
+                seed,
+                step
+            ),
+
+            (Roll::Pop, false) => assert_eq!(
+                deque.pop_back(),
+                model.pop_back(),
+                "seed {} step {}",
+                seed,
+                step
+            ),
+
+            (Roll::Drain, _) => {
+                while let Some(elem) = model.pop_back() {
+                    assert_eq!(deque.pop_back(), Some(elem), "seed {} step {}", seed, step);
+                }
+                assert_eq!(deque.pop_front(), None, "seed {} step {}", seed, step);
+            }
+        }
+
+        assert_eq!(
+            deque.peek_front().as_deref(),
+            model.front(),
+            "seed {} step {}",
+            seed,
+            step
+        );
+        assert_eq!(
+            deque.peek_back().as_deref(),
+            model.back(),
+            "seed {} step {}",
+            seed,
+            step
+        );
+    }
+
+    while let Some(elem) = model.pop_front() {
+        assert_eq!(deque.pop_front(), Some(elem), "seed {} final drain", seed);
+    }
+    assert_eq!(deque.pop_back(), None);
+}
+
+// runs `steps` random operations on a growing set of versions of a PersistentStack, each paired
+// with its own Vec. every version is rechecked at the end, so a change that leaked into an older
+// version is caught.
+pub fn persistent_stack<S: PersistentStack<i32> + Default>(seed: u64, steps: usize) {
+    let mut rng = Rng::new(seed);
+    let mut versions = vec![(S::default(), Vec::new())];
+
+    for step in 0..steps {
+        let pick = rng.below(versions.len() as u64) as usize;
+
+        let next = {
+            let (stack, model) = &versions[pick];
+
+            match roll(&mut rng) {
+                Roll::Push => {
+                    let elem = rng.next_u64() as i32;
+                    let mut model = model.clone();
+                    model.push(elem);
+                    (stack.push(elem), model)
+                }
+
+                Roll::Pop | Roll::Drain => {
+                    let mut model = model.clone();
+                    model.pop();
+                    (stack.tail(), model)
+                }
+            }
+        };
+
+        assert_eq!(next.0.head(), next.1.last(), "seed {} step {}", seed, step);
+        assert_eq!(next.0.is_empty(), next.1.is_empty());
+
+        // keep the number of live versions bounded so the check at the end stays cheap.
+        if versions.len() < 64 {
+            versions.push(next);
+        } else {
+            versions[pick] = next;
+        }
+    }
+
+    for (stack, mut model) in versions {
+        let mut stack = stack;
+        while let Some(elem) = model.pop() {
+            assert_eq!(stack.head(), Some(&elem), "seed {} final walk", seed);
+            stack = stack.tail();
+        }
+        assert_eq!(stack.head(), None);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Rng;
+
+    #[test]
+    fn rng_is_deterministic() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let mut c = Rng::new(8);
+
+        let first: Vec<_> = (0..8).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..8).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(first, (0..8).map(|_| c.next_u64()).collect::<Vec<_>>());
+
+        for _ in 0..1000 {
+            assert!(a.below(10) < 10);
+        }
+    }
+}
@@ -1,6 +1,26 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
 use std::ptr;
 
-type Link<T> = Option<Box<Node<T>>>;
+use crate::traits::Queue;
+
+// every link is a raw pointer, including head and next.
+//
+// an earlier version owned the nodes through `Option<Box<Node<T>>>` and kept a raw `tail` into
+// the last box. that is unsound under Stacked Borrows: moving or borrowing a Box asserts unique
+// access to its node, which invalidates the raw tail pointer, so the next push wrote through a
+// pointer Miri considers dead. with raw pointers everywhere there is no Box to assert uniqueness,
+// and a node is only turned back into a Box when it is freed.
+//
+// ownership is explicit instead: Box::into_raw in push hands a node to the list, Box::from_raw in
+// pop takes it back. while a node is in the list it is only reached through these raw pointers;
+// references to a node are only made for as long as a peek or an iterator borrows the list.
+//
+// CI runs these tests under Miri to check this. to run them locally:
+// `cargo +nightly miri test --lib -- fifth`.
+type Link<T> = *mut Node<T>;
 
 struct Node<T> {
     elem: T,
@@ -9,99 +29,185 @@ struct Node<T> {
 
 pub struct List<T> {
     head: Link<T>,
-    tail: *mut Node<T>, // unsafe here.
+    tail: Link<T>, // the last node, null when head is null.
 }
 
 impl<T> List<T> {
     pub fn new() -> Self {
         List {
-            head: None,
+            head: ptr::null_mut(),
             tail: ptr::null_mut(),
         }
     }
 
-    // Pushes an element to the end of the list.
-    //
-    // a lifetime of <'a> for the inner type T is declared for the impl scope
-    //
-    // function body of push() is declared &mut 'xyz' or desugared: (&'_ mut 'xyz')
-    // this declaring an anonymous lifetime that must be inferred by the compiler
-    // the function as_deref_mut() converts from and Option<T> to a Option<&mut T::Target>
-    // knowledge of Target lifetime required so reference does not outlive the target
-    //
-    // we have to specifically tell the compiler that we are borrowing from ourself and we will
-    // last as long as T exists.
+    // pushes an element to the end of the list.
     pub fn push(&mut self, elem: T) {
-        let mut new_tail = Box::new(Node { elem, next: None });
-
-        // creating a raw pointer with coercion
-        // if a variable is declared to be a raw pointer, a normal reference will coerce into it
-        let raw_tail: *mut _ = &mut *new_tail;
+        // the list takes ownership of the allocation here and gives it back in pop.
+        let new_tail = Box::into_raw(Box::new(Node {
+            elem,
+            next: ptr::null_mut(),
+        }));
 
         // equivalent for checking for None but with raw pointers.
         if !self.tail.is_null() {
-            // if the old tail existed, update it to point to the new tail
-            // derefencing a raw pointer is unsafe; unsafety block must be explicitly shown
+            // if the old tail existed, update it to point to the new tail.
             //
             // operator precedence i.e. specify which operation comes first:
             //
             // (raw_pointer_derefencing).(address field) or (first).(second)
-            unsafe { (*self.tail).next = Some(new_tail) };
+            unsafe { (*self.tail).next = new_tail };
         } else {
-            self.head = Some(new_tail);
+            self.head = new_tail;
         }
 
-        self.tail = raw_tail;
+        self.tail = new_tail;
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        self.head.take().map(|old_head| {
-            let old_head = *old_head; // derefence the boxed head.
-            self.head = old_head.next; // assign the next head to the next node of the old
+        if self.head.is_null() {
+            return None;
+        }
 
-            // if the current head is None set the tail to None.
-            if self.head.is_none() {
-                self.tail = ptr::null_mut();
-            }
+        // safe as head is non null and was made by Box::into_raw in push; no other pointer to it
+        // is used again, tail is cleared below if it pointed at the same node.
+        let old_head = unsafe { Box::from_raw(self.head) };
+        self.head = old_head.next;
 
-            old_head.elem
-        })
+        // if the current head is null set the tail to null.
+        if self.head.is_null() {
+            self.tail = ptr::null_mut();
+        }
+
+        Some(old_head.elem)
     }
 
     pub fn peek(&self) -> Option<&T> {
-        // as_ref demotes '&Option<T>' to 'Option<&T>'.
-        self.head.as_ref().map(|node| &node.elem)
+        // as_ref turns a raw pointer into Option<&T>, None when it is null.
+        unsafe { self.head.as_ref() }.map(|node| &node.elem)
     }
 
     pub fn peek_mut(&mut self) -> Option<&mut T> {
-        self.head.as_mut().map(|node| &mut node.elem)
+        unsafe { self.head.as_mut() }.map(|node| &mut node.elem)
     }
 
-    #[allow(clippy::should_implement_trait)] // remove into_iter ambiguos call warning
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
+    // the most recently pushed element, read through the tail pointer.
+    pub fn peek_back(&self) -> Option<&T> {
+        unsafe { self.tail.as_ref() }.map(|node| &node.elem)
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.tail.as_mut() }.map(|node| &mut node.elem)
+    }
+
+    // moves every element of other onto the back of self in O(1), leaving other empty.
+    //
+    // the nodes themselves don't move: the last node of self is linked to the first node of
+    // other, and other's tail becomes ours.
+    pub fn append(&mut self, other: &mut List<T>) {
+        if other.head.is_null() {
+            return;
+        }
+
+        if !self.tail.is_null() {
+            unsafe { (*self.tail).next = other.head };
+        } else {
+            self.head = other.head;
+        }
+
+        self.tail = other.tail;
+
+        // other must forget the nodes, or both lists would free them.
+        other.head = ptr::null_mut();
+        other.tail = ptr::null_mut();
     }
 
     // using Rust 2018 explicitly elided lifetime syntax.
     pub fn iter(&self) -> Iter<'_, T> {
         Iter {
-            next: self.head.as_deref(),
+            next: unsafe { self.head.as_ref() },
         }
     }
 
     pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
-            next: self.head.as_deref_mut(),
+            next: unsafe { self.head.as_mut() },
+        }
+    }
+
+    // returns a cursor that can edit the queue, starting on the ghost position before the head.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            list: self,
+            cur: ptr::null_mut(),
+            index: None,
         }
     }
 }
 
+// raw pointers are !Send and !Sync, so the list has to opt back in by hand.
+//
+// safety: the list owns every node uniquely, exactly like a chain of Boxes would. no node is
+// shared with another List or reachable through anything but this list's head and tail, and the
+// pointers are only dereferenced through &self or &mut self. so sending the list sends its Ts (which
+// needs T: Send), and sharing &List only ever hands out &T (which needs T: Sync).
+//
+// the doc tests below check the bounds aren't looser than that.
+/// ```compile_fail
+/// fn assert_send<S: Send>() {}
+/// assert_send::<lists::fifth::List<std::rc::Rc<i32>>>();
+/// ```
+unsafe impl<T: Send> Send for List<T> {}
+
+/// ```compile_fail
+/// fn assert_sync<S: Sync>() {}
+/// assert_sync::<lists::fifth::List<std::cell::Cell<i32>>>();
+/// ```
+unsafe impl<T: Sync> Sync for List<T> {}
+
 impl<T> Default for List<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+// IntoIterator is what lets a list be used directly in a for loop, or anywhere that takes
+// `impl IntoIterator`.
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+// `for elem in &list` borrows each element.
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// `for elem in &mut list` mutably borrows each element.
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// nodes are raw pointers, so nothing frees them unless they are popped back into boxes.
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
 }
@@ -111,7 +217,7 @@ impl<'a, T> Iterator for Iter<'a, T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.next.map(|node| {
-            self.next = node.next.as_deref();
+            self.next = unsafe { node.next.as_ref() };
 
             &node.elem
         })
@@ -127,7 +233,7 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     type Item = &'a mut T;
     fn next(&mut self) -> Option<Self::Item> {
         self.next.take().map(|node| {
-            self.next = node.next.as_deref_mut();
+            self.next = unsafe { node.next.as_mut() };
 
             &mut node.elem
         })
@@ -143,78 +249,500 @@ impl<T> Iterator for IntoIter<T> {
     }
 }
 
+// CursorMut edits the queue in the middle, e.g. to pull a job forward in a run-list.
+//
+// like second::CursorMut it can only move forwards, starts on a "ghost" position before the head,
+// and edits the link after the current node. the extra work here is the tail pointer: any edit
+// that adds or removes the last node has to move the list's tail too, or the next push would
+// write through a stale pointer.
+pub struct CursorMut<'a, T> {
+    list: &'a mut List<T>,
+    cur: Link<T>, // null on the ghost position.
+    index: Option<usize>,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    // index of the current element, None on the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.cur.as_mut() }.map(|node| &mut node.elem)
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe { self.next_link().as_mut() }.map(|node| &mut node.elem)
+    }
+
+    // moves onto the next node, returns false and stays put if there is no next node.
+    pub fn move_next(&mut self) -> bool {
+        let next = *self.next_link();
+        if next.is_null() {
+            return false;
+        }
+
+        self.cur = next;
+        self.index = Some(self.index.map_or(0, |index| index + 1));
+        true
+    }
+
+    // inserts an element straight after the cursor, the cursor does not move.
+    pub fn insert_after(&mut self, elem: T) {
+        let new = Box::into_raw(Box::new(Node {
+            elem,
+            next: ptr::null_mut(),
+        }));
+
+        let link = self.next_link();
+        unsafe { (*new).next = *link };
+        *link = new;
+
+        // the cursor was on the last node, or on the ghost of an empty list: either way nothing
+        // comes after the new node, so it is the new tail.
+        if self.list.tail == self.cur {
+            self.list.tail = new;
+        }
+    }
+
+    // removes the element straight after the cursor and returns it.
+    pub fn remove_after(&mut self) -> Option<T> {
+        let link = self.next_link();
+        let next = *link;
+        if next.is_null() {
+            return None;
+        }
+
+        // safe as next is non null and was made by Box::into_raw; once unlinked below nothing
+        // else points at it, apart from tail which is fixed up straight after.
+        let removed = unsafe { Box::from_raw(next) };
+        *link = removed.next;
+
+        // the last node went, so the cursor's node (or null on the ghost) is the end now.
+        if self.list.tail == next {
+            self.list.tail = self.cur;
+        }
+
+        Some(removed.elem)
+    }
+
+    // the link following the cursor, which every edit goes through.
+    fn next_link(&mut self) -> &mut Link<T> {
+        match unsafe { self.cur.as_mut() } {
+            Some(node) => &mut node.next,
+            None => &mut self.list.head,
+        }
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut len = 0;
+        for elem in self.iter() {
+            elem.hash(state);
+            len += 1;
+        }
+        state.write_usize(len);
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+// push and pop already work at opposite ends, so they are the queue's push_back and pop_front.
+impl<T> Queue<T> for List<T> {
+    type Peek<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push_back(&mut self, elem: T) {
+        self.push(elem)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek_front(&self) -> Option<&T> {
+        self.peek()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::List;
+    use crate::{conformance, differential};
+    use std::thread;
+
+    #[test]
+    fn conformance() {
+        conformance::queue::<List<i32>>();
+        conformance::queue_into_iter::<List<i32>>();
+    }
 
     #[test]
-    fn push_and_pop() {
+    fn differential() {
+        // a shorter run under miri, which is far too slow for the full one.
+        let (seeds, steps) = if cfg!(miri) { (2, 200) } else { (16, 2_000) };
+        for seed in 0..seeds {
+            differential::queue::<List<i32>>(seed, steps);
+        }
+    }
+
+    #[test]
+    fn iter() {
         let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
 
-        // check empty list behaves right
-        assert_eq!(list.pop(), None);
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), None);
+    }
 
-        // populate list.
+    #[test]
+    fn iter_mut() {
+        let mut list = List::new();
         list.push(1);
         list.push(2);
         list.push(3);
 
-        // check normal removal.
-        assert_eq!(list.pop(), Some(1));
-        assert_eq!(list.pop(), Some(2));
+        let mut iter = list.iter_mut();
+        assert_eq!(iter.next(), Some(&mut 1));
+        assert_eq!(iter.next(), Some(&mut 2));
+        assert_eq!(iter.next(), Some(&mut 3));
+        assert_eq!(iter.next(), None);
+    }
 
-        // push some more to make sure nothing is corrupted.
-        list.push(4);
-        list.push(5);
+    #[test]
+    fn std_traits() {
+        conformance::std_traits::<List<i32>>();
+    }
 
-        // check normal removal.
-        assert_eq!(list.pop(), Some(3));
-        assert_eq!(list.pop(), Some(4));
+    #[test]
+    fn for_loops() {
+        let mut list: List<_> = vec![1, 2, 3].into_iter().collect();
 
-        // check exhaustion.
-        assert_eq!(list.pop(), Some(5));
-        assert_eq!(list.pop(), None);
+        for elem in &mut list {
+            *elem *= 10;
+        }
+
+        let mut seen = Vec::new();
+        for elem in &list {
+            seen.push(*elem);
+        }
+        assert_eq!(seen, vec![10, 20, 30]);
+
+        // check lists can be passed where `impl IntoIterator` is expected.
+        let mut other = vec![0];
+        other.extend(list);
+        assert_eq!(other, vec![0, 10, 20, 30]);
     }
 
+    // the tests below mix every operation that touches the tail pointer or hands out references
+    // into the nodes, which is what Miri needs to see to check the raw pointer juggling.
+
     #[test]
-    fn into_iter() {
+    fn miri_push_pop_peek_mut() {
         let mut list = List::new();
+
         list.push(1);
         list.push(2);
         list.push(3);
 
-        let mut iter = list.into_iter();
-        assert_eq!(iter.next(), Some(1));
-        assert_eq!(iter.next(), Some(2));
-        assert_eq!(iter.next(), Some(3));
-        assert_eq!(iter.next(), None);
+        assert_eq!(list.pop(), Some(1));
+        list.push(4);
+        assert_eq!(list.pop(), Some(2));
+        list.push(5);
+
+        assert_eq!(list.peek(), Some(&3));
+        list.push(6);
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
+        assert_eq!(list.peek(), Some(&30));
+        assert_eq!(list.pop(), Some(30));
+
+        // drain to empty so the tail is reset, then make sure it is usable again.
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), Some(6));
+        assert_eq!(list.pop(), None);
+        list.push(7);
+        list.push(8);
+        assert_eq!(list.peek_mut(), Some(&mut 7));
+        assert_eq!(list.pop(), Some(7));
+        assert_eq!(list.pop(), Some(8));
+        assert_eq!(list.pop(), None);
     }
 
     #[test]
-    fn iter() {
+    fn miri_iter_mut_then_push() {
         let mut list = List::new();
+
         list.push(1);
         list.push(2);
         list.push(3);
 
+        for elem in list.iter_mut() {
+            *elem *= 100;
+        }
+
+        // the tail was reached through iter_mut, pushing must still be allowed to write to it.
+        list.push(4);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![100, 200, 300, 4]
+        );
+
+        if let Some(x) = list.peek_mut() {
+            *x += 1;
+        }
+        assert_eq!(list.pop(), Some(101));
+
+        for elem in list.iter_mut() {
+            *elem += 1;
+        }
+        list.push(5);
+
         let mut iter = list.iter();
-        assert_eq!(iter.next(), Some(&1));
-        assert_eq!(iter.next(), Some(&2));
-        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), Some(&201));
+        assert_eq!(iter.next(), Some(&301));
+        assert_eq!(iter.next(), Some(&5));
+        assert_eq!(iter.next(), Some(&5));
         assert_eq!(iter.next(), None);
     }
 
     #[test]
-    fn iter_mut() {
+    fn miri_drop_non_empty() {
+        // the nodes and their heap owned elements must be freed without being popped first.
         let mut list = List::new();
+        list.push(String::from("a"));
+        list.push(String::from("b"));
+        list.push(String::from("c"));
+        assert_eq!(list.pop().as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn peek_back() {
+        let mut list = List::new();
+        assert_eq!(list.peek_back(), None);
+        assert_eq!(list.peek_back_mut(), None);
+
         list.push(1);
+        assert_eq!(list.peek_back(), Some(&1));
         list.push(2);
-        list.push(3);
+        assert_eq!(list.peek_back(), Some(&2));
 
-        let mut iter = list.iter_mut();
-        assert_eq!(iter.next(), Some(&mut 1));
-        assert_eq!(iter.next(), Some(&mut 2));
-        assert_eq!(iter.next(), Some(&mut 3));
-        assert_eq!(iter.next(), None);
+        if let Some(x) = list.peek_back_mut() {
+            *x = 42;
+        }
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.peek_back(), Some(&42));
+        assert_eq!(list.pop(), Some(42));
+
+        // check the tail was cleared with the last pop.
+        assert_eq!(list.peek_back(), None);
+    }
+
+    #[test]
+    fn append() {
+        let mut list = List::new();
+        let mut other = List::new();
+
+        // check appending empty lists.
+        list.append(&mut other);
+        assert_eq!(list.pop(), None);
+
+        other.push(1);
+        other.push(2);
+        list.append(&mut other);
+        assert_eq!(other.pop(), None);
+        assert_eq!(other.peek_back(), None);
+        assert_eq!(list.peek_back(), Some(&2));
+
+        // check appending an empty list leaves the tail alone.
+        list.append(&mut other);
+        assert_eq!(list.peek_back(), Some(&2));
+
+        other.push(3);
+        other.push(4);
+        list.append(&mut other);
+        assert_eq!(list.peek_back(), Some(&4));
+
+        // check both lists are still usable afterwards.
+        list.push(5);
+        other.push(6);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert_eq!(other.pop(), Some(6));
+        assert_eq!(other.pop(), None);
+
+        // check draining through the appended nodes resets the tail.
+        while list.pop().is_some() {}
+        assert_eq!(list.peek_back(), None);
+        list.push(7);
+        assert_eq!(list.peek(), Some(&7));
+        assert_eq!(list.peek_back(), Some(&7));
+    }
+
+    #[test]
+    fn send_and_sync() {
+        fn assert_send<S: Send>() {}
+        fn assert_sync<S: Sync>() {}
+
+        assert_send::<List<i32>>();
+        assert_sync::<List<i32>>();
+        assert_send::<List<String>>();
+
+        // Mutex<T> is Send and Sync but only Send when T is, so this mixes the bounds.
+        assert_sync::<List<std::sync::Mutex<i32>>>();
+    }
+
+    #[test]
+    fn move_across_threads() {
+        let mut list: List<_> = (0..1000).map(|i| i.to_string()).collect();
+        list.push(String::from("last"));
+
+        // the worker gets the whole populated queue, keeps going with it and hands it back.
+        let list = thread::spawn(move || {
+            assert_eq!(list.pop().as_deref(), Some("0"));
+            list.push(String::from("from worker"));
+            list
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(list.peek().map(String::as_str), Some("1"));
+        assert_eq!(list.peek_back().map(String::as_str), Some("from worker"));
+
+        // shared borrows can be read from several threads at once.
+        let list = &list;
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(move || assert_eq!(list.iter().count(), 1001));
+            }
+        });
+    }
+
+    #[test]
+    fn cursor_mut() {
+        let mut list: List<_> = vec![1, 2, 3].into_iter().collect();
+
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+
+        // insert in the middle, then remove it again.
+        assert!(cursor.move_next());
+        cursor.insert_after(10);
+        assert_eq!(cursor.peek_next(), Some(&mut 10));
+        assert_eq!(cursor.remove_after(), Some(10));
+
+        // pull 3 in front of 2, like reprioritising a job.
+        assert!(cursor.move_next());
+        assert_eq!(cursor.index(), Some(1));
+        let job = cursor.remove_after().unwrap();
+        assert_eq!(cursor.remove_after(), None);
+        assert!(!cursor.move_next());
+
+        let mut cursor = list.cursor_mut();
+        assert!(cursor.move_next());
+        cursor.insert_after(job);
+
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 3, 2]);
+        assert_eq!(list.peek_back(), Some(&2));
+    }
+
+    #[test]
+    fn cursor_mut_keeps_tail() {
+        let mut list = List::new();
+
+        // insert into an empty queue through the ghost position.
+        list.cursor_mut().insert_after(1);
+        assert_eq!(list.peek_back(), Some(&1));
+
+        // insert after the last node.
+        let mut cursor = list.cursor_mut();
+        assert!(cursor.move_next());
+        cursor.insert_after(2);
+        assert!(cursor.move_next());
+        cursor.insert_after(3);
+        assert_eq!(list.peek_back(), Some(&3));
+        list.push(4);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+
+        // remove the last node.
+        let mut cursor = list.cursor_mut();
+        assert!(cursor.move_next());
+        assert!(cursor.move_next());
+        assert!(cursor.move_next());
+        assert_eq!(cursor.remove_after(), Some(4));
+        assert_eq!(list.peek_back(), Some(&3));
+        list.push(5);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 5]);
+
+        // remove everything through the ghost position.
+        let mut cursor = list.cursor_mut();
+        while cursor.remove_after().is_some() {}
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.peek_back(), None);
+        list.push(6);
+        assert_eq!(list.peek(), Some(&6));
+        assert_eq!(list.peek_back(), Some(&6));
+        assert_eq!(list.pop(), Some(6));
+        assert_eq!(list.pop(), None);
     }
 }
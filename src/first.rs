@@ -1,25 +1,48 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
 use std::mem;
 
+use crate::traits::Stack;
+
 // struct properties ~ to have many values at once whereas enums have 1 of several values.
 
 // tail of the List never allocates extra junk with this method.
 // enum is in null pointer optimised all elems are uniformly allocated.
 
-enum Link {
+enum Link<T> {
     Empty,
-    More(Box<Node>),
+    More(Box<Node<T>>),
+}
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
 }
 
-struct Node {
-    elem: i32,
-    next: Link,
+pub struct List<T> {
+    head: Link<T>, // can potentially be empty or hold a node.
 }
 
-pub struct List {
-    head: Link, // can potentially be empty or hold a node.
+// the same as Option's as_deref/as_deref_mut, spelled out for our own enum.
+impl<T> Link<T> {
+    fn as_ref(&self) -> Option<&Node<T>> {
+        match self {
+            Link::Empty => None,
+            Link::More(node) => Some(node),
+        }
+    }
+
+    fn as_mut(&mut self) -> Option<&mut Node<T>> {
+        match self {
+            Link::Empty => None,
+            Link::More(node) => Some(node),
+        }
+    }
 }
 
-impl List {
+impl<T> List<T> {
     pub fn new() -> Self {
         List {
             head: Link::Empty, // :: is namespace operator
@@ -27,7 +50,7 @@ impl List {
     }
 
     // pushes an element into the linked list.
-    pub fn push(&mut self, elem: i32) {
+    pub fn push(&mut self, elem: T) {
         let new_node = Box::new(Node {
             elem,
             // replace self.head temporarily with Link::Empty
@@ -39,12 +62,12 @@ impl List {
     }
 
     // pops a node from the linked list.
-    fn pop_node(&mut self) -> Link {
+    fn pop_node(&mut self) -> Link<T> {
         mem::replace(&mut self.head, Link::Empty) // returns head of list
     }
 
     // matches the popped node and returns an Option of the inner Element.
-    pub fn pop(&mut self) -> Option<i32> {
+    pub fn pop(&mut self) -> Option<T> {
         match self.pop_node() {
             Link::Empty => None,
 
@@ -54,16 +77,63 @@ impl List {
             }
         }
     }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.head.as_mut().map(|node| &mut node.elem)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_ref(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head.as_mut(),
+        }
+    }
 }
 
-impl Default for List {
+impl<T> Default for List<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
 // drops the linked list.
-impl Drop for List {
+impl<T> Drop for List<T> {
     fn drop(&mut self) {
         // pop the first node from the head of the list.
         let mut cur_link = self.pop_node();
@@ -75,36 +145,208 @@ impl Drop for List {
     }
 }
 
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_ref();
+            &node.elem
+        })
+    }
+}
+
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.take().map(|node| {
+            self.next = node.next.as_mut();
+            &mut node.elem
+        })
+    }
+}
+
+pub struct IntoIter<T>(List<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut len = 0;
+        for elem in self.iter() {
+            elem.hash(state);
+            len += 1;
+        }
+        state.write_usize(len);
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// first::List keeps no tail pointer, so extend has to walk to the end before it can add anything.
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut tail = &mut self.head;
+        while let Link::More(node) = tail {
+            tail = &mut node.next;
+        }
+
+        for elem in iter {
+            *tail = Link::More(Box::new(Node {
+                elem,
+                next: Link::Empty,
+            }));
+
+            tail = match tail {
+                Link::More(node) => &mut node.next,
+                Link::Empty => unreachable!(),
+            };
+        }
+    }
+}
+
+// the list is a stack, so the Stack methods are just the inherent ones.
+impl<T> Stack<T> for List<T> {
+    type Peek<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push(&mut self, elem: T) {
+        List::push(self, elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        List::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        List::peek(self)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::List;
+    use super::{Link, List, Node};
+    use crate::{conformance, differential};
+    use std::mem::size_of;
+
+    #[test]
+    fn conformance() {
+        conformance::stack::<List<i32>>();
+        conformance::stack_into_iter::<List<i32>>();
+    }
+
+    #[test]
+    fn differential() {
+        for seed in 0..16 {
+            differential::stack::<List<i32>>(seed, 2_000);
+        }
+    }
 
     #[test]
-    fn push_and_pop() {
+    fn std_traits() {
+        conformance::std_traits::<List<i32>>();
+    }
+
+    #[test]
+    fn peek() {
         let mut list = List::new();
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.peek_mut(), None);
 
-        // check empty list behaves right
-        assert_eq!(list.pop(), None);
+        list.push(1);
+        list.push(2);
 
-        // populate list.
+        assert_eq!(list.peek(), Some(&2));
+        if let Some(val) = list.peek_mut() {
+            *val = 42;
+        }
+        assert_eq!(list.pop(), Some(42));
+    }
+
+    #[test]
+    fn iters() {
+        let mut list = List::new();
         list.push(1);
         list.push(2);
         list.push(3);
 
-        // check normal removal.
-        assert_eq!(list.pop(), Some(3));
-        assert_eq!(list.pop(), Some(2));
+        for elem in &mut list {
+            *elem *= 10;
+        }
 
-        // push some more to make sure nothing is corrupted.
-        list.push(4);
-        list.push(5);
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&30));
+        assert_eq!(iter.next(), Some(&20));
+        assert_eq!(iter.next(), Some(&10));
+        assert_eq!(iter.next(), None);
 
-        // check normal removal.
-        assert_eq!(list.pop(), Some(5));
-        assert_eq!(list.pop(), Some(4));
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(30));
+        assert_eq!(iter.next(), Some(20));
+        assert_eq!(iter.next(), Some(10));
+        assert_eq!(iter.next(), None);
+    }
 
-        // check exhaustion.
-        assert_eq!(list.pop(), Some(1));
-        assert_eq!(list.pop(), None);
+    #[test]
+    fn null_pointer_optimised() {
+        // Empty is stored as the null Box, so a Link costs no more than the Box it may hold.
+        assert_eq!(size_of::<Link<i32>>(), size_of::<Box<Node<i32>>>());
+        assert_eq!(size_of::<Link<String>>(), size_of::<Box<Node<String>>>());
+        assert_eq!(size_of::<Link<()>>(), size_of::<usize>());
     }
 }
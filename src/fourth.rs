@@ -1,6 +1,12 @@
 use std::cell::{Ref, RefCell, RefMut};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::{self, FromIterator};
 use std::rc::Rc;
 
+use crate::traits::{Deque, Queue, Stack};
+
 pub struct List<T> {
     head: Link<T>,
     tail: Link<T>,
@@ -109,7 +115,7 @@ impl<T> List<T> {
         })
     }
 
-    pub fn peek_front(&self) -> Option<Ref<T>> {
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
         self.head.as_ref().map(|node| {
             // Ref::map docs:
             //
@@ -126,27 +132,29 @@ impl<T> List<T> {
         })
     }
 
-    pub fn peek_front_mut(&mut self) -> Option<RefMut<T>> {
+    pub fn peek_front_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.head
             .as_mut()
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
     }
 
-    pub fn peek_back(&self) -> Option<Ref<T>> {
+    pub fn peek_back(&self) -> Option<Ref<'_, T>> {
         self.tail
             .as_ref()
             .map(|node| Ref::map(node.borrow(), |node| &node.elem))
     }
 
-    pub fn peek_back_mut(&mut self) -> Option<RefMut<T>> {
+    pub fn peek_back_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.tail
             .as_mut()
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
     }
 
-    #[allow(clippy::should_implement_trait)] // remove into_iter ambiguos call warning
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
+    // walks the nodes front to back. a borrowing iterator can't hand out a Ref that outlives the
+    // Ref of the node before it, so this hands out the Rc of each node instead and callers borrow
+    // the element for as long as they need it.
+    fn nodes(&self) -> impl Iterator<Item = Rc<RefCell<Node<T>>>> {
+        iter::successors(self.head.clone(), |node| node.borrow().next.clone())
     }
 }
 
@@ -156,6 +164,17 @@ impl<T> Default for List<T> {
     }
 }
 
+// IntoIterator is what lets a list be used directly in a for loop, or anywhere that takes
+// `impl IntoIterator`.
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
         // is_some() docs:
@@ -183,36 +202,180 @@ impl<T> DoubleEndedIterator for IntoIter<T> {
     }
 }
 
-#[cfg(test)]
-mod test {
-    use super::List;
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.nodes()
+            .map(|node| node.borrow().elem.clone())
+            .collect()
+    }
+}
 
-    #[test]
-    fn push_and_pop() {
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        for node in self.nodes() {
+            list.entry(&node.borrow().elem);
+        }
+        list.finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        let mut left = self.nodes();
+        let mut right = other.nodes();
+
+        loop {
+            match (left.next(), right.next()) {
+                (None, None) => return true,
+                (Some(l), Some(r)) if l.borrow().elem == r.borrow().elem => {}
+                _ => return false,
+            }
+        }
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let mut left = self.nodes();
+        let mut right = other.nodes();
+
+        loop {
+            match (left.next(), right.next()) {
+                (None, None) => return Some(Ordering::Equal),
+                (None, Some(_)) => return Some(Ordering::Less),
+                (Some(_), None) => return Some(Ordering::Greater),
+                (Some(l), Some(r)) => match l.borrow().elem.partial_cmp(&r.borrow().elem) {
+                    Some(Ordering::Equal) => {}
+                    non_eq => return non_eq,
+                },
+            }
+        }
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let mut left = self.nodes();
+        let mut right = other.nodes();
+
+        loop {
+            match (left.next(), right.next()) {
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(l), Some(r)) => match l.borrow().elem.cmp(&r.borrow().elem) {
+                    Ordering::Equal => {}
+                    non_eq => return non_eq,
+                },
+            }
+        }
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut len = 0;
+        for node in self.nodes() {
+            node.borrow().elem.hash(state);
+            len += 1;
+        }
+        state.write_usize(len);
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
 
-        // check empty list behaves correctly
-        assert_eq!(list.pop_front(), None);
+// a doubly linked list can be used from either end, so it is a stack (working on the front), a
+// queue and a deque.
+impl<T> Stack<T> for List<T> {
+    type Peek<'a>
+        = Ref<'a, T>
+    where
+        T: 'a;
 
-        list.push_front(2);
-        list.push_front(3);
-        list.push_back(1);
+    fn push(&mut self, elem: T) {
+        self.push_front(elem)
+    }
 
-        // check normal removal
-        assert_eq!(list.pop_front(), Some(3));
-        assert_eq!(list.pop_front(), Some(2));
+    fn pop(&mut self) -> Option<T> {
+        self.pop_front()
+    }
 
-        // push more to make sure nothing is corrupted
-        list.push_front(4);
-        list.push_front(5);
+    fn peek(&self) -> Option<Ref<'_, T>> {
+        List::peek_front(self)
+    }
+}
 
-        // check normal removal
-        assert_eq!(list.pop_front(), Some(5));
-        assert_eq!(list.pop_front(), Some(4));
+impl<T> Queue<T> for List<T> {
+    type Peek<'a>
+        = Ref<'a, T>
+    where
+        T: 'a;
 
-        // check exhaustion
-        assert_eq!(list.pop_front(), Some(1));
-        assert_eq!(list.pop_front(), None);
+    fn push_back(&mut self, elem: T) {
+        List::push_back(self, elem)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        List::pop_front(self)
+    }
+
+    fn peek_front(&self) -> Option<Ref<'_, T>> {
+        List::peek_front(self)
+    }
+}
+
+impl<T> Deque<T> for List<T> {
+    fn push_front(&mut self, elem: T) {
+        List::push_front(self, elem)
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        List::pop_back(self)
+    }
+
+    fn peek_back(&self) -> Option<Ref<'_, T>> {
+        List::peek_back(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use crate::{conformance, differential};
+
+    #[test]
+    fn conformance() {
+        conformance::stack::<List<i32>>();
+        conformance::stack_into_iter::<List<i32>>();
+        conformance::deque::<List<i32>>();
+        conformance::queue_into_iter::<List<i32>>();
+    }
+
+    #[test]
+    fn differential() {
+        for seed in 0..16 {
+            differential::stack::<List<i32>>(seed, 2_000);
+            differential::queue::<List<i32>>(seed, 2_000);
+            differential::deque::<List<i32>>(seed, 2_000);
+        }
     }
 
     #[test]
@@ -253,4 +416,20 @@ mod test {
         assert_eq!(iter.next_back(), None);
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn std_traits() {
+        conformance::std_traits::<List<i32>>();
+    }
+
+    #[test]
+    fn for_loops() {
+        let list: List<_> = vec![1, 2, 3].into_iter().collect();
+
+        let mut seen = Vec::new();
+        for elem in list {
+            seen.push(elem);
+        }
+        assert_eq!(seen, vec![1, 2, 3]);
+    }
 }
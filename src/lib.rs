@@ -1,5 +1,15 @@
+pub mod async_queue;
+pub mod channel;
+pub mod conformance;
+pub mod differential;
 pub mod fifth;
 pub mod first;
 pub mod fourth;
+pub mod mpsc;
+pub mod persistent_queue;
+pub mod random_access;
 pub mod second;
+pub mod stream;
 pub mod third;
+pub mod traits;
+pub mod zipper;
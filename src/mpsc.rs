@@ -0,0 +1,550 @@
+// a lock-free multi-producer single-consumer queue (Dmitry Vyukov's intrusive design).
+//
+// it is fifth::List with the tail made atomic: the consumer owns the head and pops from it, and
+// producers append at the tail. pushing is two steps instead of one though:
+//
+//   1. swap the tail for the new node, which claims the new node's place in the queue;
+//   2. link the old tail's next to the new node.
+//
+// a producer can be paused between the two, which leaves a queue that isn't empty but whose head
+// can't reach the rest yet. pop reports that as Pop::Inconsistent rather than waiting for it.
+//
+// the queue is intrusive: it never allocates. callers hand it a Node they own, through push_node,
+// and pop_node hands the same node back, so a node can be filled and pushed again without going
+// through the allocator. push and pop are the convenient versions that box each element on the
+// way in and unbox it on the way out.
+//
+// head and tail always have to point at some node, even when the queue is empty, so the queue owns
+// one "stub" node with no elem. the stub sits at the head to begin with and is skipped when
+// popping. a node can only be handed out once something is linked after it (its next is how the
+// head moves on), so when the last node is popped the stub is pushed behind it first:
+//
+//   head -> A -> B <- tail              pop gives back A
+//   head -> B <- tail                   to give back B, push the stub:
+//   head -> B -> stub <- tail           pop gives back B
+//   head -> stub <- tail                the queue is empty again
+//
+// the queue is only reachable through a Producer, which can be cloned, and one Consumer, which
+// can't. that is what makes the consumer side single threaded without any checks.
+
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+// a queue node holding one elem. it derefs to the elem, so it can be read and refilled in place.
+pub struct Node<T> {
+    elem: Option<T>, // None only for the stub, which never leaves the queue.
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    pub fn new(elem: T) -> Box<Node<T>> {
+        Box::new(Node {
+            elem: Some(elem),
+            next: AtomicPtr::new(ptr::null_mut()),
+        })
+    }
+
+    pub fn into_inner(self) -> T {
+        self.elem.expect("only the stub has no elem")
+    }
+}
+
+impl<T> Deref for Node<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.elem.as_ref().expect("only the stub has no elem")
+    }
+}
+
+impl<T> DerefMut for Node<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.elem.as_mut().expect("only the stub has no elem")
+    }
+}
+
+struct Queue<T> {
+    head: UnsafeCell<*mut Node<T>>, // only ever touched by the consumer.
+    tail: AtomicPtr<Node<T>>,       // shared by every producer.
+    stub: *mut Node<T>,
+}
+
+// safety: nodes are owned by the queue while they are in it and handed between threads only
+// through the atomics; the consumer side (head) is only used through the single Consumer, so the
+// UnsafeCell is never accessed concurrently. elements move between threads, so T has to be Send.
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+// what a single pop saw.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Pop<T> {
+    Data(T),
+    Empty,
+    // a producer is half way through a push; trying again shortly will see the element.
+    Inconsistent,
+}
+
+impl<T> Pop<T> {
+    fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Pop<U> {
+        match self {
+            Pop::Data(elem) => Pop::Data(f(elem)),
+            Pop::Empty => Pop::Empty,
+            Pop::Inconsistent => Pop::Inconsistent,
+        }
+    }
+}
+
+impl<T> Queue<T> {
+    fn new() -> Self {
+        let stub = Box::into_raw(Box::new(Node {
+            elem: None,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }));
+
+        Queue {
+            head: UnsafeCell::new(stub),
+            tail: AtomicPtr::new(stub),
+            stub,
+        }
+    }
+
+    fn push(&self, node: Box<Node<T>>) {
+        let (prev, node) = self.claim(node);
+        unsafe { Self::link(prev, node) };
+    }
+
+    // push step 1: swaps node in as the tail, returning the old tail with it.
+    fn claim(&self, node: Box<Node<T>>) -> (*mut Node<T>, *mut Node<T>) {
+        unsafe { self.claim_raw(Box::into_raw(node)) }
+    }
+
+    // safety: node must be a valid node that isn't in the queue.
+    unsafe fn claim_raw(&self, node: *mut Node<T>) -> (*mut Node<T>, *mut Node<T>) {
+        // a node that has been through the queue before, the stub included, still points at
+        // whatever came after it last time.
+        (*node).next.store(ptr::null_mut(), Ordering::Relaxed);
+
+        // AcqRel: release publishes the new node to the next producer that swaps, acquire sees
+        // the old tail's initialisation from the producer before us.
+        let prev = self.tail.swap(node, Ordering::AcqRel);
+        (prev, node)
+    }
+
+    // push step 2: makes node reachable from the head side.
+    //
+    // safety: prev and node must be a pair returned by claim, linked exactly once. prev can't be
+    // freed before this: the consumer never hands out a node whose next is still null.
+    unsafe fn link(prev: *mut Node<T>, node: *mut Node<T>) {
+        (*prev).next.store(node, Ordering::Release);
+    }
+
+    // safety: only one thread may pop at a time.
+    unsafe fn pop(&self) -> Pop<Box<Node<T>>> {
+        let mut head = *self.head.get();
+        let mut next = (*head).next.load(Ordering::Acquire);
+
+        // the stub holds nothing, step over it.
+        if head == self.stub {
+            if next.is_null() {
+                // nothing after the stub. if the tail is still the stub the queue is really
+                // empty, otherwise some producer has claimed a place but not linked it yet.
+                return if self.tail.load(Ordering::Acquire) == head {
+                    Pop::Empty
+                } else {
+                    Pop::Inconsistent
+                };
+            }
+
+            *self.head.get() = next;
+            head = next;
+            next = (*next).next.load(Ordering::Acquire);
+        }
+
+        if !next.is_null() {
+            *self.head.get() = next;
+            return Pop::Data(Box::from_raw(head));
+        }
+
+        // head is the last linked node. if it isn't the tail as well, a producer has claimed the
+        // place after it and will link it shortly.
+        if self.tail.load(Ordering::Acquire) != head {
+            return Pop::Inconsistent;
+        }
+
+        // put the stub behind head so head has a next to move on to.
+        let (prev, stub) = self.claim_raw(self.stub);
+        Self::link(prev, stub);
+
+        next = (*head).next.load(Ordering::Acquire);
+        if !next.is_null() {
+            *self.head.get() = next;
+            return Pop::Data(Box::from_raw(head));
+        }
+
+        // a producer swapped the tail between the check above and the stub push, and hasn't
+        // linked after head yet.
+        Pop::Inconsistent
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        // no Producer or Consumer is left, so every push is complete and the chain from the head
+        // reaches every node. free them in a loop like the other lists do; the stub may or may not
+        // be in the chain, so it is freed on its own at the end.
+        let mut cur = *self.head.get_mut();
+
+        while !cur.is_null() {
+            let next = unsafe { (*cur).next.load(Ordering::Relaxed) };
+            if cur != self.stub {
+                drop(unsafe { Box::from_raw(cur) });
+            }
+            cur = next;
+        }
+
+        drop(unsafe { Box::from_raw(self.stub) });
+    }
+}
+
+// makes a new queue and returns the two ends of it.
+pub fn queue<T>() -> (Producer<T>, Consumer<T>) {
+    let queue = Arc::new(Queue::new());
+
+    (
+        Producer {
+            queue: queue.clone(),
+        },
+        Consumer { queue },
+    )
+}
+
+// the pushing end, clone it to push from more threads.
+pub struct Producer<T> {
+    queue: Arc<Queue<T>>,
+}
+
+impl<T> Producer<T> {
+    // never blocks and never fails. allocates a node for elem.
+    pub fn push(&self, elem: T) {
+        self.push_node(Node::new(elem))
+    }
+
+    // pushes a node the caller made, or got back from pop_node, without allocating.
+    pub fn push_node(&self, node: Box<Node<T>>) {
+        self.queue.push(node)
+    }
+}
+
+impl<T> Clone for Producer<T> {
+    fn clone(&self) -> Self {
+        Producer {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+// the popping end; there is only ever one, and popping takes &mut, so pops can't race.
+pub struct Consumer<T> {
+    queue: Arc<Queue<T>>,
+}
+
+impl<T> Consumer<T> {
+    // one attempt, reporting exactly what it saw, and handing back the node the elem came in.
+    pub fn try_pop_node(&mut self) -> Pop<Box<Node<T>>> {
+        unsafe { self.queue.pop() }
+    }
+
+    // pops the next node, waiting out any half finished push. None means the queue was empty.
+    pub fn pop_node(&mut self) -> Option<Box<Node<T>>> {
+        loop {
+            match self.try_pop_node() {
+                Pop::Data(node) => return Some(node),
+                Pop::Empty => return None,
+                Pop::Inconsistent => thread::yield_now(),
+            }
+        }
+    }
+
+    // like try_pop_node, but unboxes the elem.
+    pub fn try_pop(&mut self) -> Pop<T> {
+        self.try_pop_node().map(|node| node.into_inner())
+    }
+
+    // like pop_node, but unboxes the elem.
+    pub fn pop(&mut self) -> Option<T> {
+        self.pop_node().map(|node| node.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{queue, Node, Pop, Queue};
+    use std::ptr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn push_and_pop() {
+        let (producer, mut consumer) = queue();
+
+        // check empty queue behaves right.
+        assert_eq!(consumer.try_pop(), Pop::Empty);
+
+        producer.push(1);
+        producer.clone().push(2);
+        producer.push(3);
+
+        assert_eq!(consumer.pop(), Some(1));
+        assert_eq!(consumer.pop(), Some(2));
+
+        producer.push(4);
+
+        // check exhaustion.
+        assert_eq!(consumer.pop(), Some(3));
+        assert_eq!(consumer.pop(), Some(4));
+        assert_eq!(consumer.pop(), None);
+        assert_eq!(consumer.try_pop(), Pop::Empty);
+    }
+
+    // pops from a bare Queue and unboxes the elem, like Consumer::try_pop.
+    unsafe fn pop<T>(queue: &Queue<T>) -> Pop<T> {
+        queue.pop().map(|node| node.into_inner())
+    }
+
+    #[test]
+    fn half_finished_push() {
+        let queue = Queue::new();
+
+        queue.push(Node::new(1));
+        let (prev, node) = queue.claim(Node::new(2));
+        unsafe {
+            // 2 has a place in the queue but isn't linked yet. 1 can't be handed out either,
+            // the head has nowhere to move on to without 2.
+            assert_eq!(pop(&queue), Pop::Inconsistent);
+
+            Queue::link(prev, node);
+            assert_eq!(pop(&queue), Pop::Data(1));
+            assert_eq!(pop(&queue), Pop::Data(2));
+            assert_eq!(pop(&queue), Pop::Empty);
+        }
+    }
+
+    #[test]
+    fn nodes_are_reused() {
+        let (producer, mut consumer) = queue();
+
+        let node = Node::new(String::from("a"));
+        let addr: *const Node<String> = &*node;
+        producer.push_node(node);
+
+        // the same allocation comes back out, and can be refilled and pushed again.
+        let mut node = consumer.pop_node().unwrap();
+        assert!(ptr::eq(&*node, addr));
+        assert_eq!(**node, "a");
+
+        node.push('b');
+        producer.push(String::from("c"));
+        producer.push_node(node);
+
+        assert_eq!(consumer.pop(), Some(String::from("c")));
+        let node = consumer.pop_node().unwrap();
+        assert!(ptr::eq(&*node, addr));
+        assert_eq!(node.into_inner(), "ab");
+        assert_eq!(consumer.pop_node().map(|node| node.into_inner()), None);
+    }
+
+    #[test]
+    fn stub_goes_round() {
+        // emptying the queue pushes the stub back in every time, check that keeps working.
+        let (producer, mut consumer) = queue();
+
+        for round in 0..10 {
+            for elem in 0..round {
+                producer.push(elem);
+            }
+            for elem in 0..round {
+                assert_eq!(consumer.pop(), Some(elem));
+            }
+            assert_eq!(consumer.try_pop(), Pop::Empty);
+        }
+    }
+
+    #[test]
+    fn drop_frees_remaining() {
+        struct Counted(Arc<AtomicUsize>);
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let (producer, mut consumer) = queue();
+        for _ in 0..10 {
+            producer.push(Counted(drops.clone()));
+        }
+
+        drop(consumer.pop());
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+
+        // the queue goes when the last end does.
+        drop(consumer);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+        drop(producer);
+        assert_eq!(drops.load(Ordering::SeqCst), 10);
+    }
+
+    // one step of a schedule: which actor runs its next atomic step.
+    #[derive(Clone, Copy)]
+    enum Actor {
+        Producer(usize),
+        Consumer,
+    }
+
+    // every order the actors' steps could run in, given how many steps each has left.
+    fn schedules(
+        left: &mut [usize],
+        consumer_left: usize,
+        prefix: &mut Vec<Actor>,
+        out: &mut Vec<Vec<Actor>>,
+    ) {
+        if consumer_left == 0 && left.iter().all(|&n| n == 0) {
+            out.push(prefix.clone());
+            return;
+        }
+
+        for p in 0..left.len() {
+            if left[p] > 0 {
+                left[p] -= 1;
+                prefix.push(Actor::Producer(p));
+                schedules(left, consumer_left, prefix, out);
+                prefix.pop();
+                left[p] += 1;
+            }
+        }
+
+        if consumer_left > 0 {
+            prefix.push(Actor::Consumer);
+            schedules(left, consumer_left - 1, prefix, out);
+            prefix.pop();
+        }
+    }
+
+    // loom-style exhaustive check, with std only: the producers' claim and link steps and the
+    // consumer's pops are the only points where threads interact, so every possible interleaving
+    // of them is replayed against a real queue, one schedule at a time, on this thread.
+    //
+    // this covers every sequentially consistent interleaving of those steps, with each pop taken
+    // as one step. a pop that pushes the stub back touches the tail too, and a producer getting in
+    // during that push isn't replayed here. nor can it explore weaker memory orderings the way
+    // loom does; the stress test below runs the real thing on real threads, under Miri in CI.
+    #[test]
+    fn exhaustive_interleavings() {
+        // miri is far too slow for the full set, so under it every schedule of a smaller run is
+        // checked instead: one push per producer and two pops.
+        const PRODUCERS: usize = 2;
+        const PUSHES: usize = if cfg!(miri) { 1 } else { 2 };
+        const POPS: usize = if cfg!(miri) { 2 } else { 4 };
+
+        let mut all = Vec::new();
+        schedules(
+            &mut [PUSHES * 2; PRODUCERS],
+            POPS,
+            &mut Vec::new(),
+            &mut all,
+        );
+        // 6! / (2! 2! 2!) and 12! / (4! 4! 4!)
+        assert_eq!(all.len(), if cfg!(miri) { 90 } else { 34_650 });
+
+        for schedule in &all {
+            let queue = Queue::new();
+            let mut pushed = [0; PRODUCERS]; // how many pushes each producer claimed.
+            let mut pending = [None; PRODUCERS]; // a claimed but unlinked push.
+            let mut popped = [0; PRODUCERS];
+
+            let mut check = |pop: Pop<(usize, usize)>, pushed: &[usize], pending: &[Option<_>]| {
+                match pop {
+                    // each producer's elements come out in the order it pushed them.
+                    Pop::Data((p, seq)) => {
+                        assert_eq!(seq, popped[p]);
+                        popped[p] += 1;
+                    }
+
+                    // empty means every claimed push has been popped.
+                    Pop::Empty => assert_eq!(popped, pushed),
+
+                    // inconsistent means some push really is half done.
+                    Pop::Inconsistent => assert!(pending.iter().any(Option::is_some)),
+                }
+            };
+
+            for actor in schedule {
+                match *actor {
+                    Actor::Producer(p) => match pending[p].take() {
+                        None => {
+                            pending[p] = Some(queue.claim(Node::new((p, pushed[p]))));
+                            pushed[p] += 1;
+                        }
+                        Some((prev, node)) => unsafe { Queue::link(prev, node) },
+                    },
+
+                    Actor::Consumer => check(unsafe { pop(&queue) }, &pushed, &pending),
+                }
+            }
+
+            // every push has finished now, so draining must find everything that's left.
+            loop {
+                match unsafe { pop(&queue) } {
+                    Pop::Empty => break,
+                    pop => check(pop, &pushed, &pending),
+                }
+            }
+            assert_eq!(popped, [PUSHES; PRODUCERS]);
+        }
+    }
+
+    #[test]
+    fn stress() {
+        const PRODUCERS: usize = 8;
+        const PUSHES: usize = if cfg!(miri) { 50 } else { 10_000 };
+
+        let (producer, mut consumer) = queue();
+
+        let handles: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let producer = producer.clone();
+                thread::spawn(move || {
+                    for seq in 0..PUSHES {
+                        producer.push((p, seq));
+                    }
+                })
+            })
+            .collect();
+        drop(producer);
+
+        // pop while the producers are still going, checking per producer order.
+        let mut next = [0; PRODUCERS];
+        let mut total = 0;
+        while total < PRODUCERS * PUSHES {
+            match consumer.try_pop() {
+                Pop::Data((p, seq)) => {
+                    assert_eq!(seq, next[p]);
+                    next[p] += 1;
+                    total += 1;
+                }
+                Pop::Empty | Pop::Inconsistent => thread::yield_now(),
+            }
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(consumer.pop(), None);
+    }
+}
@@ -0,0 +1,344 @@
+// a persistent FIFO queue made of a lazy front and a strict rear (the "banker's queue" from
+// Okasaki's purely functional data structures).
+//
+// elements are taken from the head of `front`, a stream::Stream, and added to the head of `rear`, a
+// third::List, so rear holds the newest elements in reverse:
+//
+//   queue  = 1, 2, 3, 4, 5
+//   front -> 1 -> 2 -> 3
+//   rear  -> 5 -> 4
+//
+// snoc and tail are append and tail on one of the two, and return a new queue sharing both with
+// the old one. whenever rear would become longer than front the two are rotated: front becomes
+// front followed by rear reversed. the rotation is a stream too, so making it is O(1) and each of
+// its elements is only worked out when a head or tail gets to it, one step at a time.
+//
+// that laziness is what makes snoc, head and tail O(1) amortized for every version, not just along
+// a single history. the rotation is only paid for by walking to the end of it, and the stream
+// memoizes each step, so every version that shares the rotated front shares the work done on it.
+// calling tail over and over on a version that is about to rotate only makes a new, unforced
+// rotation each time.
+
+use std::fmt;
+
+use crate::stream::{self, Stream};
+use crate::third::List;
+
+pub struct Queue<T> {
+    front: Stream<T>,
+    front_len: usize,
+    rear: List<T>,
+    rear_len: usize,
+}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        Queue {
+            front: Stream::new(),
+            front_len: 0,
+            rear: List::new(),
+            rear_len: 0,
+        }
+    }
+
+    // the oldest element. rear is never longer than front, so if the queue isn't empty front isn't
+    // either and this never has to look at rear.
+    pub fn head(&self) -> Option<&T> {
+        self.front.head()
+    }
+
+    pub fn len(&self) -> usize {
+        self.front_len + self.rear_len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // oldest to newest.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.front.iter(),
+            rear: self.rear.iter().collect(),
+        }
+    }
+}
+
+// the rotation keeps its elements in stream cells, so like Stream they have to be 'static.
+impl<T: Clone + 'static> Queue<T> {
+    // returns a new queue with elem added at the back.
+    pub fn snoc(&self, elem: T) -> Queue<T> {
+        Queue::balanced(
+            self.front.clone(),
+            self.front_len,
+            self.rear.append(elem),
+            self.rear_len + 1,
+        )
+    }
+
+    // returns the queue with the oldest element removed.
+    pub fn tail(&self) -> Queue<T> {
+        if self.is_empty() {
+            return Queue::new();
+        }
+
+        Queue::balanced(
+            self.front.tail(),
+            self.front_len - 1,
+            self.rear.clone(),
+            self.rear_len,
+        )
+    }
+
+    // keeps rear no longer than front by moving rear, reversed, to the end of front. rear only ever
+    // grows or front shrinks by one at a time, so rear is exactly one longer when this rotates.
+    fn balanced(front: Stream<T>, front_len: usize, rear: List<T>, rear_len: usize) -> Queue<T> {
+        if rear_len <= front_len {
+            return Queue {
+                front,
+                front_len,
+                rear,
+                rear_len,
+            };
+        }
+
+        Queue {
+            front: rotate(front, rear, Stream::new()),
+            front_len: front_len + rear_len,
+            rear: List::new(),
+            rear_len: 0,
+        }
+    }
+}
+
+// front followed by rear reversed, followed by done, worked out one element per step. every step
+// moves the head of rear onto done, so by the time front runs out rear is down to its oldest
+// element, which goes in front of done. done is built strictly, but a step at a time, so no step
+// costs more than O(1).
+fn rotate<T: Clone + 'static>(front: Stream<T>, rear: List<T>, done: Stream<T>) -> Stream<T> {
+    Stream::lazy(move || {
+        let newest = rear
+            .head()
+            .expect("a rotation starts with rear one longer than front")
+            .clone();
+
+        match front.head() {
+            None => Some((newest, done)),
+            Some(elem) => Some((
+                elem.clone(),
+                rotate(front.tail(), rear.tail(), done.append(newest)),
+            )),
+        }
+    })
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// like third::List and Stream, cloning shares everything and doesn't need T: Clone.
+impl<T> Clone for Queue<T> {
+    fn clone(&self) -> Self {
+        Queue {
+            front: self.front.clone(),
+            front_len: self.front_len,
+            rear: self.rear.clone(),
+            rear_len: self.rear_len,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Queue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// two queues are equal if they hold the same elements, however they are split between front and
+// rear.
+impl<T: PartialEq> PartialEq for Queue<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for Queue<T> {}
+
+impl<'a, T> IntoIterator for &'a Queue<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// walking front forces whatever part of a rotation hasn't been worked out yet.
+pub struct Iter<'a, T> {
+    front: stream::Iter<'a, T>,
+    // rear's elements newest first, so popping off the end gives them oldest first.
+    rear: Vec<&'a T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.rear.pop())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Queue;
+    use crate::differential::Rng;
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    #[test]
+    fn snoc_and_tail() {
+        let queue = Queue::new();
+
+        // check empty queue behaves right.
+        assert_eq!(queue.head(), None);
+        assert!(queue.tail().is_empty());
+
+        let queue = queue.snoc(1).snoc(2).snoc(3);
+        assert_eq!(queue.head(), Some(&1));
+        assert_eq!(queue.len(), 3);
+
+        let queue = queue.tail();
+        assert_eq!(queue.head(), Some(&2));
+
+        let queue = queue.snoc(4);
+        assert_eq!(format!("{:?}", queue), "[2, 3, 4]");
+
+        // check exhaustion.
+        let queue = queue.tail().tail();
+        assert_eq!(queue.head(), Some(&4));
+        let queue = queue.tail();
+        assert_eq!(queue.head(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn versions() {
+        let base = Queue::new().snoc(1).snoc(2);
+        let a = base.snoc(3);
+        let b = base.tail().snoc(30);
+
+        assert_eq!(format!("{:?}", base), "[1, 2]");
+        assert_eq!(format!("{:?}", a), "[1, 2, 3]");
+        assert_eq!(format!("{:?}", b), "[2, 30]");
+
+        // equal however the elements are split between front and rear.
+        assert_eq!(a.tail(), Queue::new().snoc(2).snoc(3));
+    }
+
+    #[test]
+    fn shares_structure() {
+        let queue = (0..8).fold(Queue::new(), |queue, elem| queue.snoc(elem));
+
+        // a snoc that doesn't rotate leaves front alone.
+        let next = queue.tail().snoc(8);
+        assert!(next.front.ptr_eq(&queue.front.tail()));
+
+        // a tail that doesn't rotate just moves along front.
+        let next = queue.tail();
+        assert!(next.front.ptr_eq(&queue.front.tail()));
+        assert!(next.rear.ptr_eq(&queue.rear));
+    }
+
+    // counts its clones, which is how the rotation copies elements out of rear and front.
+    #[derive(Debug)]
+    struct Counted {
+        elem: usize,
+        clones: Rc<Cell<usize>>,
+    }
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.clones.set(self.clones.get() + 1);
+            Counted {
+                elem: self.elem,
+                clones: self.clones.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn rotation_is_lazy_and_shared() {
+        let clones = Rc::new(Cell::new(0));
+        let queue = (0..2_046).fold(Queue::new(), |queue, elem| {
+            queue.snoc(Counted {
+                elem,
+                clones: clones.clone(),
+            })
+        });
+
+        // rear has caught up with front, so the next tail rotates. force everything so far first.
+        assert_eq!((queue.front_len, queue.rear_len), (1_023, 1_023));
+        assert_eq!(queue.iter().count(), 2_046);
+        clones.set(0);
+
+        // taking the tail of the same version over and over only starts a rotation each time, and
+        // looking at its head works out one step of it.
+        for _ in 0..1_000 {
+            let next = queue.tail();
+            assert_eq!(next.head().map(|c| c.elem), Some(1));
+        }
+        assert!(clones.get() <= 2 * 1_000, "{} clones", clones.get());
+
+        // once a rotation has been walked, every version built on it shares the work.
+        let next = queue.tail();
+        assert_eq!(next.iter().count(), 2_045);
+        clones.set(0);
+
+        let (a, b) = (next.tail(), next.clone().tail());
+        assert!(a.front.ptr_eq(&b.front));
+        assert!(a.iter().map(|c| c.elem).eq(2..2_046));
+        assert!(b.iter().map(|c| c.elem).eq(2..2_046));
+        assert_eq!(clones.get(), 0);
+    }
+
+    #[test]
+    fn differential() {
+        // random snocs and tails on random versions, each checked against its own VecDeque.
+        for seed in 0..16 {
+            let mut rng = Rng::new(seed);
+            let mut versions = vec![(Queue::new(), VecDeque::new())];
+
+            for step in 0..2_000 {
+                let pick = rng.below(versions.len() as u64) as usize;
+                let (queue, model) = &versions[pick];
+                let mut model = model.clone();
+
+                let queue = if rng.below(100) < 55 {
+                    let elem = rng.next_u64() as i32;
+                    model.push_back(elem);
+                    queue.snoc(elem)
+                } else {
+                    model.pop_front();
+                    queue.tail()
+                };
+
+                assert_eq!(queue.head(), model.front(), "seed {} step {}", seed, step);
+                assert_eq!(queue.len(), model.len());
+                assert!(queue.rear_len <= queue.front_len);
+
+                if versions.len() < 64 {
+                    versions.push((queue, model));
+                } else {
+                    versions[pick] = (queue, model);
+                }
+            }
+
+            for (queue, model) in &versions {
+                assert!(queue.iter().eq(model.iter()), "seed {} final walk", seed);
+            }
+        }
+    }
+}
@@ -0,0 +1,434 @@
+// a persistent list with the same append/tail/head as third::List, but where getting or setting
+// the element at an index is O(log n) instead of O(n) (okasaki's skew binary random access list).
+//
+// the elements are kept in complete binary trees, each holding 2^k - 1 elements in preorder: the
+// root first, then the left subtree, then the right one. the trees are strung together on a spine
+// (a third::List) smallest first, and no two trees have the same size except possibly the first
+// two:
+//
+//   [0, 1, 2, 3, 4, 5, 6, 7]
+//
+//   spine -> (1) -> (7)
+//             0      1
+//                   / \
+//                  2   5
+//                 / \ / \
+//                3  4 6  7
+//
+// append either makes a new one element tree, or, if the first two trees are the same size, joins
+// them under a new root. tail does the opposite. both only touch the front of the spine, so both
+// are O(1). a lookup walks the spine, at most log n trees, then down one tree, at most log n deep.
+//
+// nodes are shared through Rc exactly like in third.rs: the spine is a third::List, so it gets its
+// sharing and its non-recursive Drop from there. the trees are dropped recursively, but they are
+// never more than log n deep.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+use crate::third;
+use crate::traits::PersistentStack;
+
+pub struct List<T> {
+    spine: third::List<(usize, Rc<Tree<T>>)>, // (elements in the tree, tree)
+}
+
+enum Tree<T> {
+    Leaf(T),
+    Node(T, Rc<Tree<T>>, Rc<Tree<T>>),
+}
+
+impl<T> Tree<T> {
+    fn elem(&self) -> &T {
+        match self {
+            Tree::Leaf(elem) | Tree::Node(elem, _, _) => elem,
+        }
+    }
+
+    // the element at index in a tree of size elements, walking down without recursing.
+    fn get(mut tree: &Tree<T>, mut size: usize, mut index: usize) -> &T {
+        loop {
+            match tree {
+                Tree::Node(_, left, right) if index > 0 => {
+                    // each subtree holds half of what is left after the root.
+                    size /= 2;
+                    if index <= size {
+                        tree = left;
+                        index -= 1;
+                    } else {
+                        tree = right;
+                        index -= 1 + size;
+                    }
+                }
+
+                _ => return tree.elem(),
+            }
+        }
+    }
+}
+
+impl<T: Clone> Tree<T> {
+    // a copy of the tree with the element at index replaced. only the nodes on the path down to it
+    // are copied, every subtree hanging off that path is shared with the old tree.
+    fn set(tree: &Tree<T>, mut size: usize, mut index: usize, elem: T) -> Tree<T> {
+        let mut path = Vec::new(); // (node, went left) from the root down.
+        let mut cur = tree;
+
+        while let Tree::Node(_, left, right) = cur {
+            if index == 0 {
+                break;
+            }
+
+            size /= 2;
+            if index <= size {
+                path.push((cur, true));
+                cur = left;
+                index -= 1;
+            } else {
+                path.push((cur, false));
+                cur = right;
+                index -= 1 + size;
+            }
+        }
+
+        let mut new = match cur {
+            Tree::Leaf(_) => Tree::Leaf(elem),
+            Tree::Node(_, left, right) => Tree::Node(elem, left.clone(), right.clone()),
+        };
+
+        // rebuild the path bottom up, each parent pointing at the new child.
+        for (parent, went_left) in path.into_iter().rev() {
+            if let Tree::Node(elem, left, right) = parent {
+                let child = Rc::new(new);
+                new = if went_left {
+                    Tree::Node(elem.clone(), child, right.clone())
+                } else {
+                    Tree::Node(elem.clone(), left.clone(), child)
+                };
+            }
+        }
+
+        new
+    }
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List {
+            spine: third::List::new(),
+        }
+    }
+
+    // returns a new list with elem in front of this one.
+    pub fn append(&self, elem: T) -> List<T> {
+        let rest = self.spine.tail();
+
+        // two trees the same size at the front are joined under elem.
+        if let (Some((size, left)), Some((other, right))) = (self.spine.head(), rest.head()) {
+            if size == other {
+                let tree = Tree::Node(elem, left.clone(), right.clone());
+                return List {
+                    spine: rest.tail().append((1 + size + other, Rc::new(tree))),
+                };
+            }
+        }
+
+        List {
+            spine: self.spine.append((1, Rc::new(Tree::Leaf(elem)))),
+        }
+    }
+
+    // returns the list with the first element removed.
+    pub fn tail(&self) -> List<T> {
+        let spine = match self.spine.head() {
+            None => third::List::new(),
+
+            // a tree loses its root by putting its two subtrees on the spine in its place.
+            Some((size, tree)) => match &**tree {
+                Tree::Leaf(_) => self.spine.tail(),
+                Tree::Node(_, left, right) => self
+                    .spine
+                    .tail()
+                    .append((size / 2, right.clone()))
+                    .append((size / 2, left.clone())),
+            },
+        };
+
+        List { spine }
+    }
+
+    // gets the head of the list.
+    pub fn head(&self) -> Option<&T> {
+        self.spine.head().map(|(_, tree)| tree.elem())
+    }
+
+    // the element at index, in O(log n).
+    pub fn get(&self, mut index: usize) -> Option<&T> {
+        for (size, tree) in self.spine.iter() {
+            if index < *size {
+                return Some(Tree::get(tree, *size, index));
+            }
+            index -= size;
+        }
+
+        None
+    }
+
+    // adds up the tree sizes, so this is O(log n) too.
+    pub fn len(&self) -> usize {
+        self.spine.iter().map(|(size, _)| size).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spine.head().is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            spine: self.spine.iter(),
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl<T: Clone> List<T> {
+    // returns a new list with the element at index replaced, in O(log n). the trees before the one
+    // holding index are put back on a copy of the front of the spine; everything else is shared.
+    //
+    // like indexing a slice, this panics if index is out of bounds.
+    pub fn set(&self, index: usize, elem: T) -> List<T> {
+        let mut offset = index;
+
+        for (position, (size, tree)) in self.spine.iter().enumerate() {
+            if offset < *size {
+                let tree = Rc::new(Tree::set(tree, *size, offset, elem));
+                let rest = self.spine.drop(position + 1).append((*size, tree));
+
+                return List {
+                    spine: self.spine.take(position).concat(&rest),
+                };
+            }
+            offset -= size;
+        }
+
+        panic!(
+            "index out of bounds: the len is {} but the index is {}",
+            self.len(),
+            index
+        );
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// cloning shares every tree, so it is O(1) and doesn't need T: Clone.
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            spine: self.spine.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// two lists with the same elements always have the same trees, but comparing elements is simpler.
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let elems: Vec<T> = iter.into_iter().collect();
+        elems
+            .into_iter()
+            .rev()
+            .fold(List::new(), |list, elem| list.append(elem))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// walks each tree in preorder, which is index order, with a stack of the subtrees still to visit.
+pub struct Iter<'a, T> {
+    spine: third::Iter<'a, (usize, Rc<Tree<T>>)>,
+    stack: Vec<&'a Tree<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = match self.stack.pop() {
+            Some(tree) => tree,
+            None => &**self.spine.next().map(|(_, tree)| tree)?,
+        };
+
+        if let Tree::Node(_, left, right) = tree {
+            self.stack.push(right);
+            self.stack.push(left);
+        }
+
+        Some(tree.elem())
+    }
+}
+
+impl<T> PersistentStack<T> for List<T> {
+    fn push(&self, elem: T) -> Self {
+        self.append(elem)
+    }
+
+    fn tail(&self) -> Self {
+        List::tail(self)
+    }
+
+    fn head(&self) -> Option<&T> {
+        List::head(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{List, Tree};
+    use crate::differential::Rng;
+    use crate::{conformance, differential};
+    use std::rc::Rc;
+
+    #[test]
+    fn conformance() {
+        conformance::persistent_stack::<List<i32>>();
+    }
+
+    #[test]
+    fn differential() {
+        for seed in 0..16 {
+            differential::persistent_stack::<List<i32>>(seed, 2_000);
+        }
+    }
+
+    #[test]
+    fn shape() {
+        // the list drawn at the top of the file.
+        let list: List<_> = (0..8).collect();
+        let sizes: Vec<_> = list.spine.iter().map(|(size, _)| *size).collect();
+        assert_eq!(sizes, vec![1, 7]);
+
+        // popping the leaf leaves the tree, popping the tree's root leaves its subtrees.
+        let sizes: Vec<_> = list.tail().spine.iter().map(|(size, _)| *size).collect();
+        assert_eq!(sizes, vec![7]);
+        let sizes: Vec<_> = list
+            .tail()
+            .tail()
+            .spine
+            .iter()
+            .map(|(size, _)| *size)
+            .collect();
+        assert_eq!(sizes, vec![3, 3]);
+        assert_eq!(format!("{:?}", list.tail().tail()), "[2, 3, 4, 5, 6, 7]");
+    }
+
+    #[test]
+    fn get() {
+        let list: List<_> = (0..100).collect();
+        assert_eq!(list.len(), 100);
+
+        for i in 0..100 {
+            assert_eq!(list.get(i), Some(&i));
+        }
+        assert_eq!(list.get(100), None);
+        assert_eq!(List::<i32>::new().get(0), None);
+    }
+
+    #[test]
+    fn set() {
+        let list: List<_> = (0..10).collect();
+        let changed = list.set(0, 100).set(5, 105).set(9, 109);
+
+        assert_eq!(
+            format!("{:?}", changed),
+            "[100, 1, 2, 3, 4, 105, 6, 7, 8, 109]"
+        );
+        assert_eq!(list, (0..10).collect());
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn set_out_of_bounds() {
+        let list: List<_> = (0..3).collect();
+        list.set(3, 0);
+    }
+
+    #[test]
+    fn set_shares_structure() {
+        // spine: (1), (1), (3), (15), so index 0 is in the first tree.
+        let list: List<_> = (0..20).collect();
+        let changed = list.set(0, 100);
+        assert!(changed.spine.tail().ptr_eq(&list.spine.tail()));
+
+        // index 15 is in the right subtree of the last tree: the spine in front of it is copied,
+        // the untouched left subtree is shared.
+        let changed = list.set(15, 100);
+        assert_eq!(changed.spine.shares_tail_with(&list.spine), None);
+
+        let (_, old) = list.spine.iter().nth(3).unwrap();
+        let (_, new) = changed.spine.iter().nth(3).unwrap();
+        match (&**old, &**new) {
+            (Tree::Node(_, old_left, old_right), Tree::Node(_, new_left, new_right)) => {
+                assert!(Rc::ptr_eq(old_left, new_left));
+                assert!(!Rc::ptr_eq(old_right, new_right));
+            }
+            _ => panic!("expected a node"),
+        }
+    }
+
+    #[test]
+    fn random_get_and_set() {
+        let mut rng = Rng::new(0);
+        let mut model: Vec<u64> = (0..1_000).collect();
+        let mut list: List<_> = model.iter().copied().collect();
+        let original = list.clone();
+
+        for _ in 0..2_000 {
+            let index = rng.below(model.len() as u64) as usize;
+            if rng.below(2) == 0 {
+                assert_eq!(list.get(index), Some(&model[index]));
+            } else {
+                let elem = rng.next_u64();
+                list = list.set(index, elem);
+                model[index] = elem;
+            }
+        }
+
+        assert!(list.iter().eq(model.iter()));
+        assert!(original.iter().copied().eq(0..1_000));
+    }
+
+    #[test]
+    fn long_list() {
+        let list: List<u32> = (0..1_000_000).collect();
+
+        assert_eq!(list.len(), 1_000_000);
+        assert_eq!(list.get(999_999), Some(&999_999));
+        assert!(format!("{:?}", list).ends_with("999999]"));
+    }
+}
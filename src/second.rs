@@ -1,3 +1,10 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::{FromIterator, FusedIterator};
+
+use crate::traits::Stack;
+
 // Link is generic over T and it contains an Option-Box-Generic Node.
 type Link<T> = Option<Box<Node<T>>>;
 
@@ -10,13 +17,23 @@ struct Node<T> {
 // List is generic over T.
 pub struct List<T> {
     head: Link<T>,
+    len: usize, // number of nodes, kept up to date by everything that links or unlinks them.
 }
 
 impl<T> List<T> {
     // Self refers the the object that is after the impl statement, thus we do not need to change
     // method signature when implementing generically.
     pub fn new() -> Self {
-        List { head: None }
+        List { head: None, len: 0 }
+    }
+
+    // returns the number of elements in O(1).
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     // pushes an element into the linked list.
@@ -28,6 +45,7 @@ impl<T> List<T> {
 
         // replace head of list with new node.
         self.head = Some(new_node);
+        self.len += 1;
     }
 
     // pops a node from the linked list.
@@ -38,6 +56,7 @@ impl<T> List<T> {
     pub fn pop(&mut self) -> Option<T> {
         self.head.take().map(|node| {
             self.head = node.next;
+            self.len -= 1;
             node.elem
         })
     }
@@ -51,23 +70,118 @@ impl<T> List<T> {
         self.head.as_mut().map(|node| &mut node.elem)
     }
 
-    #[allow(clippy::should_implement_trait)] // remove into_iter ambiguos call warning
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
-    }
-
     // using Rust 2018 explicitly elided lifetime syntax.
     pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             next: self.head.as_deref(),
+            len: self.len,
         }
     }
 
     pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             next: self.head.as_deref_mut(),
+            len: self.len,
         }
     }
+
+    // returns a read-only cursor sitting on the "ghost" position just before the head.
+    pub fn cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            head: self.head.as_deref(),
+            cur: None,
+            index: None,
+        }
+    }
+
+    // returns a cursor that can edit the list, starting on the ghost position before the head.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            head: Some(&mut self.head),
+            len: &mut self.len,
+            cur: None,
+            index: None,
+        }
+    }
+
+    // sorts the list in place, equal elements keep their original order.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(|a, b| a.cmp(b));
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    // bottom-up merge sort that only relinks the existing boxes.
+    //
+    // slots[i] holds a sorted run of 2^i nodes or nothing. nodes are taken off the head one at a
+    // time and carried up through the slots like incrementing a binary counter, merging with every
+    // full slot on the way. there is no recursion, and 64 slots are enough for any usize length.
+    //
+    // a higher slot always holds nodes that came earlier in the list, so it is always passed as
+    // the left side of merge() to keep the sort stable.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut slots: [Link<T>; 64] = std::array::from_fn(|_| None);
+        let mut rest = self.head.take();
+
+        while let Some(mut node) = rest {
+            rest = node.next.take();
+
+            let mut carry = Some(node);
+            let mut i = 0;
+            while let Some(run) = slots[i].take() {
+                carry = merge(Some(run), carry, &mut compare);
+                i += 1;
+            }
+            slots[i] = carry;
+        }
+
+        // fold the leftover runs together, from the newest (smallest) to the oldest.
+        let mut sorted = None;
+        for slot in slots.iter_mut() {
+            sorted = merge(slot.take(), sorted, &mut compare);
+        }
+
+        self.head = sorted;
+    }
+}
+
+// merges two sorted chains into one, preferring left on ties.
+fn merge<T, F>(mut left: Link<T>, mut right: Link<T>, compare: &mut F) -> Link<T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut merged = None;
+    let mut tail = &mut merged;
+
+    while let (Some(l), Some(r)) = (&left, &right) {
+        // only take from the right when it is strictly smaller, that is what keeps it stable.
+        let from = if compare(&r.elem, &l.elem) == Ordering::Less {
+            &mut right
+        } else {
+            &mut left
+        };
+
+        let mut node = from.take().unwrap();
+        *from = node.next.take();
+        tail = &mut tail.insert(node).next;
+    }
+
+    // whatever is left over is already sorted, so hang it off the end.
+    *tail = if left.is_some() { left } else { right };
+
+    merged
 }
 
 impl<T> Default for List<T> {
@@ -76,6 +190,37 @@ impl<T> Default for List<T> {
     }
 }
 
+// IntoIterator is what lets a list be used directly in a for loop, or anywhere that takes
+// `impl IntoIterator`.
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+// `for elem in &list` borrows each element.
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// `for elem in &mut list` mutably borrows each element.
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
 // generically implement a Drop for a generic List.
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
@@ -93,6 +238,7 @@ impl<T> Drop for List<T> {
 // inner reference to the Node of the same type.
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
+    len: usize, // elements left to yield.
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
@@ -105,15 +251,26 @@ impl<'a, T> Iterator for Iter<'a, T> {
             // Leaves the original Option in-place, creating a new one with a reference
             // to the original one, additionally coercing the contents via [`Deref`].
             self.next = node.next.as_deref();
+            self.len -= 1;
 
             &node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
 }
 
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+// once next is None it stays None, so the iterator is fused for free.
+impl<T> FusedIterator for Iter<'_, T> {}
+
 // IterMut iterates over &mut T.
 pub struct IterMut<'a, T> {
     next: Option<&'a mut Node<T>>,
+    len: usize,
 }
 
 impl<'a, T> Iterator for IterMut<'a, T> {
@@ -134,12 +291,21 @@ impl<'a, T> Iterator for IterMut<'a, T> {
             // as_deref_mut() Converts from `Option<T>` (or `&mut Option<T>`) to
             // `Option<&mut T::Target>`.
             self.next = node.next.as_deref_mut();
+            self.len -= 1;
 
             &mut node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
 }
 
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
 // IntoIter iterates over T.
 // Tuple structs are an alternative form of struct, useful for trivial wrappers around other types
 // and accessible via the dot notation i.e. 'variable.0'.
@@ -152,39 +318,276 @@ impl<T> Iterator for IntoIter<T> {
     fn next(&mut self) -> Option<Self::Item> {
         self.0.pop() // use internal pop method to take ownership of internal Node.
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use super::List;
+impl<T> ExactSizeIterator for IntoIter<T> {}
 
-    #[test]
-    fn push_and_pop() {
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for elem in self.iter() {
+            elem.hash(state);
+        }
+        state.write_usize(self.len);
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
 
-        // check empty list behaves right
-        assert_eq!(list.pop(), None);
+// a cursor walks to the last node and inserts after it, keeping len in step.
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut cursor = self.cursor_mut();
+        while cursor.move_next() {}
 
-        // populate list.
-        list.push(1);
-        list.push(2);
-        list.push(3);
+        for elem in iter {
+            cursor.insert_after(elem);
+            cursor.move_next();
+        }
+    }
+}
 
-        // check normal removal.
-        assert_eq!(list.pop(), Some(3));
-        assert_eq!(list.pop(), Some(2));
+// Cursor walks a list one node at a time without consuming it.
+//
+// a singly linked list has no back pointers, so a cursor can only ever move forwards. it starts
+// on a "ghost" position before the head, where current() is None and peek_next() is the head.
+pub struct Cursor<'a, T> {
+    head: Option<&'a Node<T>>,
+    cur: Option<&'a Node<T>>,
+    index: Option<usize>,
+}
 
-        // push some more to make sure nothing is corrupted.
-        list.push(4);
-        list.push(5);
+impl<'a, T> Cursor<'a, T> {
+    // index of the current element, None on the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
 
-        // check normal removal.
-        assert_eq!(list.pop(), Some(5));
-        assert_eq!(list.pop(), Some(4));
+    pub fn current(&self) -> Option<&'a T> {
+        self.cur.map(|node| &node.elem)
+    }
 
-        // check exhaustion.
-        assert_eq!(list.pop(), Some(1));
-        assert_eq!(list.pop(), None);
+    pub fn peek_next(&self) -> Option<&'a T> {
+        self.next_node().map(|node| &node.elem)
+    }
+
+    // moves onto the next node, returns false and stays put if there is no next node.
+    pub fn move_next(&mut self) -> bool {
+        match self.next_node() {
+            Some(node) => {
+                self.cur = Some(node);
+                self.index = Some(self.index.map_or(0, |index| index + 1));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn next_node(&self) -> Option<&'a Node<T>> {
+        match self.cur {
+            Some(node) => node.next.as_deref(),
+            None => self.head,
+        }
+    }
+}
+
+// CursorMut is a cursor that can edit the list around its position.
+//
+// every edit happens on the link *after* the current node, which is the only link a singly linked
+// list can reach in O(1). on the ghost position that link is the head of the list itself.
+//
+// only one of `head` and `cur` is ever Some: the head link is given up the first time the cursor
+// moves onto a node, because the node is borrowed out of it.
+//
+// the list's len is borrowed alongside so that edits made through the cursor keep it correct.
+pub struct CursorMut<'a, T> {
+    head: Option<&'a mut Link<T>>,
+    len: &'a mut usize,
+    cur: Option<&'a mut Node<T>>,
+    index: Option<usize>,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    // index of the current element, None on the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.cur.as_mut().map(|node| &mut node.elem)
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.next_link().as_mut().map(|node| &mut node.elem)
+    }
+
+    // moves onto the next node, returns false and stays put if there is no next node.
+    pub fn move_next(&mut self) -> bool {
+        if self.next_link().is_none() {
+            return false;
+        }
+
+        // checking first means the borrow of the next link never has to be given back, which is
+        // what lets the new &'a mut Node outlive this call.
+        self.cur = match self.cur.take() {
+            Some(node) => node.next.as_deref_mut(),
+            None => self.head.take().and_then(|head| head.as_deref_mut()),
+        };
+        self.index = Some(self.index.map_or(0, |index| index + 1));
+
+        true
+    }
+
+    // inserts an element straight after the cursor, the cursor does not move.
+    pub fn insert_after(&mut self, elem: T) {
+        let link = self.next_link();
+
+        *link = Some(Box::new(Node {
+            elem,
+            next: link.take(),
+        }));
+        *self.len += 1;
+    }
+
+    // removes the element straight after the cursor and returns it.
+    pub fn remove_after(&mut self) -> Option<T> {
+        let link = self.next_link();
+
+        let elem = link.take().map(|node| {
+            *link = node.next;
+            node.elem
+        });
+
+        if elem.is_some() {
+            *self.len -= 1;
+        }
+
+        elem
+    }
+
+    // cuts the list after the cursor, returning everything that followed it as a new list.
+    pub fn split_after(&mut self) -> List<T> {
+        // everything up to and including the current node stays behind.
+        let kept = self.index.map_or(0, |index| index + 1);
+        let head = self.next_link().take();
+
+        let len = *self.len - kept;
+        *self.len = kept;
+
+        List { head, len }
+    }
+
+    // moves every element of other in straight after the cursor, keeping their order.
+    pub fn splice_after(&mut self, mut other: List<T>) {
+        let mut other_head = other.head.take();
+
+        // walk to the last link of other; this is O(len(other)) as there is no tail pointer.
+        let mut other_tail = &mut other_head;
+        while let Some(node) = other_tail {
+            other_tail = &mut node.next;
+        }
+
+        let link = self.next_link();
+        *other_tail = link.take();
+        *link = other_head;
+
+        *self.len += other.len;
+        other.len = 0;
+    }
+
+    // the link following the cursor, which every edit goes through.
+    fn next_link(&mut self) -> &mut Link<T> {
+        match (&mut self.cur, &mut self.head) {
+            (Some(node), _) => &mut node.next,
+            (None, Some(head)) => head,
+            (None, None) => unreachable!("cursor lost both its head and current node"),
+        }
+    }
+}
+
+// the list is a stack, so the Stack methods are just the inherent ones.
+impl<T> Stack<T> for List<T> {
+    type Peek<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push(&mut self, elem: T) {
+        List::push(self, elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        List::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        List::peek(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        List::is_empty(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use crate::{conformance, differential};
+
+    #[test]
+    fn conformance() {
+        conformance::stack::<List<i32>>();
+        conformance::stack_into_iter::<List<i32>>();
+    }
+
+    #[test]
+    fn differential() {
+        for seed in 0..16 {
+            differential::stack::<List<i32>>(seed, 2_000);
+        }
     }
 
     #[test]
@@ -201,30 +604,14 @@ mod test {
         assert_eq!(list.peek_mut(), Some(&mut 3));
 
         // test the mutation. can do any of the ways below.
-        list.peek_mut().map(|val| {
+        if let Some(val) = list.peek_mut() {
             *val = 42;
-        });
+        }
 
         assert_eq!(list.peek(), Some(&42));
         assert_eq!(list.pop(), Some(42));
     }
 
-    #[test]
-    fn into_iter() {
-        let mut list = List::new();
-
-        list.push(1);
-        list.push(2);
-        list.push(3);
-
-        // test that you get ownership to the inner value wrapped in an option.
-        let mut iter = list.into_iter();
-        assert_eq!(iter.next(), Some(3));
-        assert_eq!(iter.next(), Some(2));
-        assert_eq!(iter.next(), Some(1));
-        assert_eq!(iter.next(), None);
-    }
-
     #[test]
     fn iter() {
         let mut list = List::new();
@@ -254,4 +641,265 @@ mod test {
         assert_eq!(iter.next(), Some(&mut 2));
         assert_eq!(iter.next(), Some(&mut 1));
     }
+
+    #[test]
+    fn cursor() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        // starts on the ghost position before the head.
+        let mut cursor = list.cursor();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&3));
+
+        assert!(cursor.move_next());
+        assert_eq!(cursor.current(), Some(&3));
+        assert!(cursor.move_next());
+        assert!(cursor.move_next());
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&1));
+
+        // check the cursor stays put at the end.
+        assert!(!cursor.move_next());
+        assert_eq!(cursor.current(), Some(&1));
+        assert_eq!(cursor.peek_next(), None);
+    }
+
+    #[test]
+    fn cursor_mut_insert_and_remove() {
+        let mut list = List::new();
+
+        // check editing an empty list through the ghost position.
+        let mut cursor = list.cursor_mut();
+        assert!(!cursor.move_next());
+        assert_eq!(cursor.remove_after(), None);
+        cursor.insert_after(1);
+        cursor.insert_after(3);
+        assert!(cursor.move_next());
+        cursor.insert_after(2);
+        assert_eq!(cursor.current(), Some(&mut 3));
+
+        // check the edits landed in the right order.
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+
+        let mut cursor = list.cursor_mut();
+        assert!(cursor.move_next());
+        assert_eq!(cursor.remove_after(), Some(2));
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        if let Some(val) = cursor.peek_next() {
+            *val = 42;
+        }
+        assert!(cursor.move_next());
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.remove_after(), None);
+
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(42));
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        assert_eq!(list.len(), 3);
+
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.len(), 2);
+
+        // check the cursor edits keep the count right.
+        let mut cursor = list.cursor_mut();
+        cursor.insert_after(4);
+        assert!(cursor.move_next());
+        assert_eq!(cursor.remove_after(), Some(2));
+        assert_eq!(cursor.remove_after(), Some(1));
+        assert_eq!(cursor.remove_after(), None);
+        assert_eq!(list.len(), 1);
+
+        let mut other = List::new();
+        other.push(6);
+        other.push(5);
+
+        let mut cursor = list.cursor_mut();
+        assert!(cursor.move_next());
+        cursor.splice_after(other);
+        assert_eq!(list.len(), 3);
+
+        let mut cursor = list.cursor_mut();
+        assert!(cursor.move_next());
+        let back = cursor.split_after();
+        assert_eq!(list.len(), 1);
+        assert_eq!(back.len(), 2);
+
+        // check exhaustion.
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn exact_size_iter() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+
+        let mut iter = list.iter_mut();
+        iter.next();
+        iter.next();
+        assert_eq!(iter.len(), 1);
+
+        // check it stays exhausted.
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.by_ref().count(), 3);
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn cursor_mut_split_and_splice() {
+        let mut list = List::new();
+        list.push(4);
+        list.push(1);
+
+        let mut other = List::new();
+        other.push(3);
+        other.push(2);
+
+        // splice 2 -> 3 in between 1 and 4.
+        let mut cursor = list.cursor_mut();
+        assert!(cursor.move_next());
+        cursor.splice_after(other);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3, &4]);
+
+        // splicing an empty list changes nothing.
+        let mut cursor = list.cursor_mut();
+        cursor.splice_after(List::new());
+        assert!(cursor.move_next());
+        assert!(cursor.move_next());
+
+        // split after 2.
+        let mut back = cursor.split_after();
+        assert_eq!(cursor.peek_next(), None);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2]);
+        assert_eq!(back.pop(), Some(3));
+        assert_eq!(back.pop(), Some(4));
+        assert_eq!(back.pop(), None);
+
+        // splitting on the ghost position takes the whole list.
+        let mut all = list.cursor_mut().split_after();
+        assert_eq!(list.peek(), None);
+        assert_eq!(all.pop(), Some(1));
+    }
+
+    #[test]
+    fn sort() {
+        let mut list = List::new();
+
+        // check sorting empty and single element lists.
+        list.sort();
+        assert_eq!(list.peek(), None);
+        list.push(1);
+        list.sort();
+        assert_eq!(list.peek(), Some(&1));
+
+        for elem in &[5, 3, 8, 1, 9, 2, 7, 3] {
+            list.push(*elem);
+        }
+
+        list.sort();
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![1, 1, 2, 3, 3, 5, 7, 8, 9]
+        );
+
+        list.sort_by(|a, b| b.cmp(a));
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![9, 8, 7, 5, 3, 3, 2, 1, 1]
+        );
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        let mut list = List::new();
+
+        // pushed in reverse so the list reads (0, 'a'), (1, 'b'), ... from the head.
+        for (i, key) in "abcabcabcab"
+            .chars()
+            .enumerate()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+        {
+            list.push((i, key));
+        }
+
+        list.sort_by_key(|&(_, key)| key);
+
+        // equal keys must come out in their original order.
+        assert_eq!(
+            list.iter().map(|&(i, _)| i).collect::<Vec<_>>(),
+            vec![0, 3, 6, 9, 1, 4, 7, 10, 2, 5, 8]
+        );
+    }
+
+    #[test]
+    fn sort_long_list() {
+        let mut list = List::new();
+
+        // a long list would overflow the stack if the sort recursed per node.
+        for i in 0..1_000_000u32 {
+            list.push(i.wrapping_mul(2_654_435_761) % 1000);
+        }
+
+        list.sort();
+
+        let mut iter = list.iter();
+        let mut prev = iter.next().unwrap();
+        for elem in iter {
+            assert!(prev <= elem);
+            prev = elem;
+        }
+    }
+
+    #[test]
+    fn std_traits() {
+        conformance::std_traits::<List<i32>>();
+    }
+
+    #[test]
+    fn for_loops() {
+        let mut list: List<_> = vec![1, 2, 3].into_iter().collect();
+
+        for elem in &mut list {
+            *elem *= 10;
+        }
+
+        let mut seen = Vec::new();
+        for elem in &list {
+            seen.push(*elem);
+        }
+        assert_eq!(seen, vec![10, 20, 30]);
+
+        // check lists can be passed where `impl IntoIterator` is expected.
+        let mut other = vec![0];
+        other.extend(list);
+        assert_eq!(other, vec![0, 10, 20, 30]);
+    }
 }
@@ -0,0 +1,453 @@
+// a persistent list whose tails are only worked out when something looks at them, and only once.
+//
+// each stream is a pointer to a lazy cell holding either a thunk (a closure that makes the next
+// node) or the node it made. looking at the head forces the cell: the thunk runs and the node is
+// stored in its place. after that the cell behaves exactly like a third::List node: clones of the
+// stream point at the same cell and share the forced node and everything after it.
+//
+//   s -> [1] -> [2] -> [thunk]      s.tail().tail().head() forces the third cell
+//   s -> [1] -> [2] -> [3] -> [thunk]
+//
+// because the rest is only made on demand, streams can be infinite: iterate, unfold and cycle make
+// endless ones, and map, filter and zip are lazy too, doing work only for elements someone asks
+// for. the closures involved are stored in the cells, so elements and functions have to be 'static.
+
+use std::cell::{Cell, OnceCell};
+use std::fmt;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+use crate::traits::PersistentStack;
+
+pub struct Stream<T> {
+    cell: Link<T>, // None is the empty stream.
+}
+
+type Link<T> = Option<Rc<Lazy<T>>>;
+
+type Thunk<T> = Box<dyn FnOnce() -> Option<(T, Stream<T>)>>;
+
+struct Lazy<T> {
+    value: OnceCell<Option<Node<T>>>,
+    thunk: Cell<Option<Thunk<T>>>, // taken out when the value is made.
+}
+
+struct Node<T> {
+    elem: T,
+    next: Stream<T>,
+}
+
+impl<T> Stream<T> {
+    pub fn new() -> Self {
+        Stream { cell: None }
+    }
+
+    // returns a new stream with elem in front of this one. nothing is left to work out for the
+    // new cell, so it starts out forced.
+    pub fn append(&self, elem: T) -> Stream<T> {
+        let node = Node {
+            elem,
+            next: self.clone(),
+        };
+
+        Stream {
+            cell: Some(Rc::new(Lazy {
+                value: OnceCell::from(Some(node)),
+                thunk: Cell::new(None),
+            })),
+        }
+    }
+
+    // gets the head of the stream, forcing it if it hasn't been yet.
+    pub fn head(&self) -> Option<&T> {
+        self.force().map(|node| &node.elem)
+    }
+
+    // returns the stream with the first element removed, forcing the head but not the tail.
+    pub fn tail(&self) -> Stream<T> {
+        self.force()
+            .map_or_else(Stream::new, |node| node.next.clone())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.force().is_none()
+    }
+
+    // true if both streams are the very same cell, forced or not, like third::List::ptr_eq. two
+    // empty streams are.
+    pub fn ptr_eq(&self, other: &Stream<T>) -> bool {
+        match (&self.cell, &other.cell) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    // walks the stream, forcing each element as it gets to it. on an infinite stream this never
+    // ends on its own, so use take or similar.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: Some(self) }
+    }
+
+    // runs the thunk the first time, and hands out the stored node after that.
+    fn force(&self) -> Option<&Node<T>> {
+        let lazy = self.cell.as_ref()?;
+
+        lazy.value
+            .get_or_init(|| {
+                let thunk = lazy
+                    .thunk
+                    .take()
+                    .expect("a stream's tail depends on itself");
+
+                thunk().map(|(elem, next)| Node { elem, next })
+            })
+            .as_ref()
+    }
+}
+
+impl<T: 'static> Stream<T> {
+    // a stream whose first node is only made when something looks at it. f returns the head and
+    // the rest of the stream, or None for an empty stream.
+    pub fn lazy<F: FnOnce() -> Option<(T, Stream<T>)> + 'static>(f: F) -> Self {
+        Stream {
+            cell: Some(Rc::new(Lazy {
+                value: OnceCell::new(),
+                thunk: Cell::new(Some(Box::new(f))),
+            })),
+        }
+    }
+
+    // state, f turns it into an element and the next state, until it returns None.
+    pub fn unfold<S: 'static, F: Fn(S) -> Option<(T, S)> + 'static>(state: S, f: F) -> Self {
+        fn go<T: 'static, S: 'static, F: Fn(S) -> Option<(T, S)> + 'static>(
+            state: S,
+            f: Rc<F>,
+        ) -> Stream<T> {
+            Stream::lazy(move || {
+                let (elem, state) = f(state)?;
+                Some((elem, go(state, f)))
+            })
+        }
+
+        go(state, Rc::new(f))
+    }
+
+    // a lazy stream of f applied to each element.
+    pub fn map<U: 'static, F: Fn(&T) -> U + 'static>(&self, f: F) -> Stream<U> {
+        fn go<T: 'static, U: 'static, F: Fn(&T) -> U + 'static>(
+            source: Stream<T>,
+            f: Rc<F>,
+        ) -> Stream<U> {
+            Stream::lazy(move || {
+                let node = source.force()?;
+                Some((f(&node.elem), go(node.next.clone(), f)))
+            })
+        }
+
+        go(self.clone(), Rc::new(f))
+    }
+}
+
+impl<T: Clone + 'static> Stream<T> {
+    // seed, f(seed), f(f(seed)), and so on forever. each f runs when its element is first asked
+    // for, not when the one before it is.
+    pub fn iterate<F: Fn(&T) -> T + 'static>(seed: T, f: F) -> Self {
+        fn after<T: Clone + 'static, F: Fn(&T) -> T + 'static>(prev: T, f: Rc<F>) -> Stream<T> {
+            Stream::lazy(move || {
+                let elem = f(&prev);
+                Some((elem.clone(), after(elem, f)))
+            })
+        }
+
+        after(seed.clone(), Rc::new(f)).append(seed)
+    }
+
+    // this stream over and over again, or an empty stream if it is empty.
+    //
+    // an Rc list can't point back at its own start without leaking, so this makes new nodes for
+    // every round. holding on to the head of a cycle while walking it keeps all of them alive.
+    pub fn cycle(&self) -> Stream<T> {
+        fn go<T: Clone + 'static>(cur: Stream<T>, start: Stream<T>) -> Stream<T> {
+            Stream::lazy(move || {
+                let node = cur.force().or_else(|| start.force())?;
+                Some((node.elem.clone(), go(node.next.clone(), start.clone())))
+            })
+        }
+
+        go(self.clone(), self.clone())
+    }
+
+    // a lazy stream of the elements p accepts. forcing it walks the source until the next match,
+    // so on an infinite stream with no more matches it never returns.
+    pub fn filter<P: Fn(&T) -> bool + 'static>(&self, p: P) -> Stream<T> {
+        fn go<T: Clone + 'static, P: Fn(&T) -> bool + 'static>(
+            source: Stream<T>,
+            p: Rc<P>,
+        ) -> Stream<T> {
+            Stream::lazy(move || {
+                let mut cur = source;
+                loop {
+                    let node = cur.force()?;
+                    if p(&node.elem) {
+                        return Some((node.elem.clone(), go(node.next.clone(), p)));
+                    }
+
+                    let next = node.next.clone();
+                    cur = next;
+                }
+            })
+        }
+
+        go(self.clone(), Rc::new(p))
+    }
+
+    // pairs up the elements of both streams, lazily, stopping at the end of the shorter one.
+    pub fn zip<U: Clone + 'static>(&self, other: &Stream<U>) -> Stream<(T, U)> {
+        fn go<T: Clone + 'static, U: Clone + 'static>(
+            a: Stream<T>,
+            b: Stream<U>,
+        ) -> Stream<(T, U)> {
+            Stream::lazy(move || {
+                let (x, y) = (a.force()?, b.force()?);
+                Some((
+                    (x.elem.clone(), y.elem.clone()),
+                    go(x.next.clone(), y.next.clone()),
+                ))
+            })
+        }
+
+        go(self.clone(), other.clone())
+    }
+}
+
+impl<T> Default for Stream<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// cloning shares the cells, forced or not, so forcing through one clone forces it for all of them.
+impl<T> Clone for Stream<T> {
+    fn clone(&self) -> Self {
+        Stream {
+            cell: self.cell.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Stream<T> {
+    fn drop(&mut self) {
+        // the same loop as third::List: keep unwrapping cells nobody else points at. an unforced
+        // cell ends it, its thunk gets dropped normally.
+        let mut cell = self.cell.take();
+
+        while let Some(lazy) = cell {
+            match Rc::try_unwrap(lazy) {
+                Ok(lazy) => {
+                    cell = lazy
+                        .value
+                        .into_inner()
+                        .flatten()
+                        .and_then(|mut node| node.next.cell.take())
+                }
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+// shows what has been forced so far and doesn't force anything, so it works on infinite streams.
+// an unforced rest is shown as "..".
+impl<T: fmt::Debug> fmt::Debug for Stream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        let mut cur = self;
+
+        while let Some(lazy) = &cur.cell {
+            match lazy.value.get() {
+                Some(Some(node)) => {
+                    list.entry(&node.elem);
+                    cur = &node.next;
+                }
+                Some(None) => break,
+                None => {
+                    list.entry(&format_args!(".."));
+                    break;
+                }
+            }
+        }
+
+        list.finish()
+    }
+}
+
+// collecting is strict: it reads the whole iterator and keeps its order, so the first item ends up
+// at the head. use unfold to wrap an iterator lazily.
+impl<T> FromIterator<T> for Stream<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let elems: Vec<T> = iter.into_iter().collect();
+        elems
+            .into_iter()
+            .rev()
+            .fold(Stream::new(), |stream, elem| stream.append(elem))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Stream<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Stream<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?.force();
+        self.next = node.map(|node| &node.next);
+        node.map(|node| &node.elem)
+    }
+}
+
+impl<T> PersistentStack<T> for Stream<T> {
+    fn push(&self, elem: T) -> Self {
+        self.append(elem)
+    }
+
+    fn tail(&self) -> Self {
+        Stream::tail(self)
+    }
+
+    fn head(&self) -> Option<&T> {
+        Stream::head(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Stream;
+    use crate::{conformance, differential};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn first<T: Clone>(stream: &Stream<T>, n: usize) -> Vec<T> {
+        stream.iter().take(n).cloned().collect()
+    }
+
+    #[test]
+    fn conformance() {
+        conformance::persistent_stack::<Stream<i32>>();
+    }
+
+    #[test]
+    fn differential() {
+        for seed in 0..16 {
+            differential::persistent_stack::<Stream<i32>>(seed, 2_000);
+        }
+    }
+
+    #[test]
+    fn iterate_and_unfold() {
+        let powers = Stream::iterate(1u64, |x| x * 2);
+        assert_eq!(first(&powers, 5), vec![1, 2, 4, 8, 16]);
+
+        let fib = Stream::unfold((0u64, 1u64), |(a, b)| Some((a, (b, a + b))));
+        assert_eq!(first(&fib, 8), vec![0, 1, 1, 2, 3, 5, 8, 13]);
+
+        // unfold can end.
+        let countdown = Stream::unfold(3, |n| if n > 0 { Some((n, n - 1)) } else { None });
+        assert_eq!(first(&countdown, 10), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn cycle() {
+        let stream: Stream<_> = vec![1, 2, 3].into_iter().collect();
+        assert_eq!(first(&stream.cycle(), 7), vec![1, 2, 3, 1, 2, 3, 1]);
+        assert!(Stream::<i32>::new().cycle().is_empty());
+    }
+
+    #[test]
+    fn map_filter_zip() {
+        let naturals = Stream::iterate(0u64, |x| x + 1);
+
+        let squares = naturals.map(|x| x * x);
+        assert_eq!(first(&squares, 5), vec![0, 1, 4, 9, 16]);
+
+        let even = naturals.filter(|x| x % 2 == 0);
+        assert_eq!(first(&even, 4), vec![0, 2, 4, 6]);
+
+        let pairs = even.zip(&squares);
+        assert_eq!(first(&pairs, 3), vec![(0, 0), (2, 1), (4, 4)]);
+
+        // zip stops at the shorter stream.
+        let short: Stream<_> = vec!['a', 'b'].into_iter().collect();
+        assert_eq!(first(&naturals.zip(&short), 5), vec![(0, 'a'), (1, 'b')]);
+    }
+
+    #[test]
+    fn lazy_and_memoized() {
+        let calls = Rc::new(Cell::new(0));
+
+        let counted = calls.clone();
+        let stream = Stream::iterate(0u64, |x| x + 1).map(move |x| {
+            counted.set(counted.get() + 1);
+            x * 10
+        });
+
+        // nothing runs until something looks.
+        assert_eq!(calls.get(), 0);
+        assert_eq!(format!("{:?}", stream), "[..]");
+
+        assert_eq!(first(&stream, 3), vec![0, 10, 20]);
+        assert_eq!(calls.get(), 3);
+        assert_eq!(format!("{:?}", stream), "[0, 10, 20, ..]");
+
+        // forcing again, through a clone, reuses what was worked out.
+        let clone = stream.clone();
+        assert_eq!(first(&clone, 3), vec![0, 10, 20]);
+        assert_eq!(calls.get(), 3);
+
+        assert_eq!(clone.tail().tail().tail().head(), Some(&30));
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn forced_nodes_are_shared() {
+        let stream = Stream::iterate(0, |x| x + 1);
+        let a = stream.tail().tail();
+        let b = stream.tail().tail();
+
+        assert!(Rc::ptr_eq(
+            a.cell.as_ref().unwrap(),
+            b.cell.as_ref().unwrap()
+        ));
+
+        // appending shares the rest like third::List does.
+        let c = Stream::new().append(1);
+        let d = c.append(2);
+        assert!(Rc::ptr_eq(
+            c.cell.as_ref().unwrap(),
+            d.tail().cell.as_ref().unwrap()
+        ));
+    }
+
+    #[test]
+    fn long_stream() {
+        // recursing once per node while dropping would overflow the stack here.
+        let stream = Stream::iterate(0u64, |x| x + 1);
+        assert_eq!(stream.iter().nth(1_000_000), Some(&1_000_000));
+        assert!(format!("{:?}", stream).ends_with("1000000, ..]"));
+        drop(stream);
+
+        let list: Stream<u32> = (0..1_000_000).collect();
+        assert_eq!(list.iter().count(), 1_000_000);
+    }
+}
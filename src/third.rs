@@ -14,14 +14,28 @@
 //               |
 // list3 -> X ---+
 
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::{self, FromIterator};
 use std::rc::Rc;
 
+use crate::traits::PersistentStack;
+
+// hash-consing, so equal lists can share every node.
+pub mod intern;
+// a Send + Sync copy of this list, built on Arc.
+pub mod sync;
+
 pub struct List<T> {
     head: Link<T>,
 }
 
 type Link<T> = Option<Rc<Node<T>>>;
 
+// Clone on a node copies its elem and shares its next, which is what Rc::make_mut needs to give
+// the mutating methods below their own copy of a shared node.
+#[derive(Clone)]
 struct Node<T> {
     elem: T,
     next: Link<T>,
@@ -66,6 +80,201 @@ impl<T> List<T> {
     pub fn head(&self) -> Option<&T> {
         self.head.as_ref().map(|node| &node.elem)
     }
+
+    // like append, but changes this list instead of returning a new one. the old head is moved
+    // into the new node rather than cloned, so no reference count changes.
+    pub fn push_mut(&mut self, elem: T) {
+        self.head = Some(Rc::new(Node {
+            elem,
+            next: self.head.take(),
+        }));
+    }
+}
+
+// ways to see the sharing drawn at the top of this file, for debugging what keeps nodes alive and
+// for checking that an operation didn't copy.
+impl<T> List<T> {
+    // true if both lists are the very same nodes, not just equal elements. two empty lists are.
+    pub fn ptr_eq(&self, other: &List<T>) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    // how many nodes at the end the two lists physically share, or None if they share none.
+    //
+    // once two lists reach a common node they share everything after it too, so the shared part
+    // is always a suffix. skipping the longer list's extra front nodes lines the two up, then they
+    // are walked side by side until they meet.
+    pub fn shares_tail_with(&self, other: &List<T>) -> Option<usize> {
+        let (len, other_len) = (self.nodes().count(), other.nodes().count());
+
+        let mut a = self.nodes().skip(len.saturating_sub(other_len));
+        let mut b = other.nodes().skip(other_len.saturating_sub(len));
+        let mut left = len.min(other_len);
+
+        while let (Some(x), Some(y)) = (a.next(), b.next()) {
+            if Rc::ptr_eq(x, y) {
+                return Some(left);
+            }
+            left -= 1;
+        }
+
+        None
+    }
+
+    // how many lists and nodes point at the node at index, or None if the list is shorter.
+    pub fn strong_count_at(&self, index: usize) -> Option<usize> {
+        self.nodes().nth(index).map(Rc::strong_count)
+    }
+
+    // how many nodes at the front only this list can reach. everything from the first shared node
+    // on is reachable by some other list as well, even nodes whose own count is 1.
+    pub fn unique_prefix_len(&self) -> usize {
+        self.nodes()
+            .take_while(|node| Rc::strong_count(node) == 1)
+            .count()
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &Rc<Node<T>>> {
+        iter::successors(self.head.as_ref(), |node| node.next.as_ref())
+    }
+}
+
+// the methods below only copy when they have to. a node whose Rc is unique belongs to this list
+// alone and is changed in place; a node that is shared with another list is cloned first, which is
+// why they need T: Clone.
+impl<T: Clone> List<T> {
+    // like tail, but changes this list and hands back the head. a unique head node is unwrapped and
+    // its elem moved out, a shared one is left alone for the other lists and its elem cloned.
+    pub fn pop_mut(&mut self) -> Option<T> {
+        self.head.take().map(|node| match Rc::try_unwrap(node) {
+            Ok(node) => {
+                self.head = node.next;
+                node.elem
+            }
+            Err(node) => {
+                self.head = node.next.clone();
+                node.elem.clone()
+            }
+        })
+    }
+
+    // iterates mutably, copying nodes on write. the first shared node and every node after it are
+    // visible to another list, so each of those is copied as the iterator reaches it; stopping
+    // early leaves the rest of the list shared.
+    pub fn iter_mut_cow(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head.as_mut(),
+        }
+    }
+}
+
+// persistent combinators. each returns a new list and leaves this one alone; wherever the result
+// ends in nodes this list (or other) already has, it points at them instead of copying.
+impl<T> List<T> {
+    // builds a list of the items in order and hangs `tail` off the last one, so the result shares
+    // every node of tail.
+    fn from_iter_with_tail<I: IntoIterator<Item = T>>(iter: I, tail: Link<T>) -> List<T> {
+        let mut head = None;
+        let mut last = &mut head;
+
+        // the nodes are built front to back, which is only allowed because nobody else can see
+        // them yet: Rc::get_mut always succeeds on a freshly made Rc.
+        for elem in iter {
+            let node = last.insert(Rc::new(Node { elem, next: None }));
+            last = &mut Rc::get_mut(node).unwrap().next;
+        }
+        *last = tail;
+
+        List { head }
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> List<U> {
+        self.iter().map(f).collect()
+    }
+
+    // the list without its first n elements. this is just n tails, so it shares every node.
+    pub fn drop(&self, n: usize) -> List<T> {
+        let mut link = &self.head;
+        for _ in 0..n {
+            match link {
+                Some(node) => link = &node.next,
+                None => break,
+            }
+        }
+
+        List { head: link.clone() }
+    }
+
+    pub fn fold_left<B, F: FnMut(B, &T) -> B>(&self, init: B, f: F) -> B {
+        self.iter().fold(init, f)
+    }
+
+    // folds from the last element back to the first. the textbook version recurses down the list
+    // and folds on the way back up, which would overflow the stack on a long list, so this collects
+    // the elements first and walks them backwards instead.
+    pub fn fold_right<B, F: FnMut(&T, B) -> B>(&self, init: B, mut f: F) -> B {
+        let elems: Vec<&T> = self.iter().collect();
+        elems.into_iter().rev().fold(init, |acc, elem| f(elem, acc))
+    }
+}
+
+// the combinators that have to build new nodes holding this list's elements clone them into place.
+impl<T: Clone> List<T> {
+    // keeps the elements p accepts. everything after the last rejected element is kept as it is,
+    // so that part is shared rather than copied.
+    pub fn filter<P: FnMut(&T) -> bool>(&self, mut p: P) -> List<T> {
+        let mut kept = Vec::new();
+        let mut copied = 0; // how many of kept come before the shared part.
+        let mut shared = &self.head;
+
+        let mut link = &self.head;
+        while let Some(node) = link {
+            if p(&node.elem) {
+                kept.push(&node.elem);
+            } else {
+                copied = kept.len();
+                shared = &node.next;
+            }
+            link = &node.next;
+        }
+
+        List::from_iter_with_tail(
+            kept[..copied].iter().map(|&elem| elem.clone()),
+            shared.clone(),
+        )
+    }
+
+    pub fn reverse(&self) -> List<T> {
+        let mut reversed = List::new();
+        for elem in self.iter() {
+            reversed.push_mut(elem.clone());
+        }
+        reversed
+    }
+
+    // this list followed by other. this list's nodes are copied because their last next has to
+    // change, but other's are shared.
+    pub fn concat(&self, other: &List<T>) -> List<T> {
+        List::from_iter_with_tail(self.iter().cloned(), other.head.clone())
+    }
+
+    // the first n elements. if that is the whole list it is shared, otherwise they are copied.
+    pub fn take(&self, n: usize) -> List<T> {
+        if self.iter().nth(n).is_none() {
+            return self.clone();
+        }
+
+        self.iter().take(n).cloned().collect()
+    }
+
+    // pairs up the elements of both lists, stopping at the end of the shorter one.
+    pub fn zip<U: Clone>(&self, other: &List<U>) -> List<(T, U)> {
+        self.iter().cloned().zip(other.iter().cloned()).collect()
+    }
 }
 
 impl<T> Default for List<T> {
@@ -74,6 +283,17 @@ impl<T> Default for List<T> {
     }
 }
 
+// IntoIterator for &List is what lets `for elem in &list` borrow each element.
+// a persistent list can't be consumed or mutated, so there is no owned or &mut version.
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
 }
@@ -90,6 +310,24 @@ impl<'a, T> Iterator for Iter<'a, T> {
     }
 }
 
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut Rc<Node<T>>>,
+}
+
+impl<'a, T: Clone> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.take().map(|node| {
+            // make_mut clones the node only if another list can see it. the clone shares the old
+            // node's next, so that one is now shared too and gets copied on the following call.
+            let node = Rc::make_mut(node);
+            self.next = node.next.as_mut();
+            &mut node.elem
+        })
+    }
+}
+
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
         // hoist the current head into scope through option
@@ -114,32 +352,97 @@ impl<T> Drop for List<T> {
     }
 }
 
-#[cfg(test)]
-mod test {
-    use super::List;
+// cloning a persistent list is just another handle on the same nodes, so it is O(1) and doesn't
+// need T: Clone.
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+        }
+    }
+}
 
-    #[test]
-    fn heads_and_tails() {
-        let list = List::new();
-        assert_eq!(list.head(), None);
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
 
-        // returns a new list instead of mutating.
-        let list = list.append(1).append(2).append(3);
-        assert_eq!(list.head(), Some(&3));
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
 
-        // remove the head of the list.
-        let list = list.tail();
-        assert_eq!(list.head(), Some(&2));
+impl<T: Eq> Eq for List<T> {}
 
-        let list = list.tail();
-        assert_eq!(list.head(), Some(&1));
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
 
-        let list = list.tail();
-        assert_eq!(list.head(), None);
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
 
-        // assert that empty tail works correctly.
-        let list = list.tail();
-        assert_eq!(list.head(), None);
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut len = 0;
+        for elem in self.iter() {
+            elem.hash(state);
+            len += 1;
+        }
+        state.write_usize(len);
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        List::from_iter_with_tail(iter, None)
+    }
+}
+
+// extend adds the items after the last element. the existing nodes may be shared with other lists
+// and can't be changed, so they are copied first, which makes this O(n) in the current length.
+impl<T: Clone> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        *self = self.iter().cloned().chain(iter).collect();
+    }
+}
+
+impl<T> PersistentStack<T> for List<T> {
+    fn push(&self, elem: T) -> Self {
+        self.append(elem)
+    }
+
+    fn tail(&self) -> Self {
+        List::tail(self)
+    }
+
+    fn head(&self) -> Option<&T> {
+        List::head(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use crate::{conformance, differential};
+    use std::rc::Rc;
+
+    #[test]
+    fn conformance() {
+        conformance::persistent_stack::<List<i32>>();
+    }
+
+    #[test]
+    fn differential() {
+        for seed in 0..16 {
+            differential::persistent_stack::<List<i32>>(seed, 2_000);
+        }
     }
 
     #[test]
@@ -152,4 +455,236 @@ mod test {
         assert_eq!(iter.next(), Some(&1));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn std_traits() {
+        conformance::std_traits::<List<i32>>();
+    }
+
+    #[test]
+    fn extend_copies() {
+        let list: List<_> = vec![1, 2, 3].into_iter().collect();
+
+        // check extend appends without touching the lists it shares nodes with.
+        let mut longer = list.clone();
+        longer.extend(vec![4, 5]);
+        assert_eq!(format!("{:?}", longer), "[1, 2, 3, 4, 5]");
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        assert_eq!(longer.shares_tail_with(&list), None);
+    }
+
+    // the address of the head node, to tell a node changed in place from a copy.
+    fn head_ptr<T>(list: &List<T>) -> *const super::Node<T> {
+        list.head.as_ref().map_or(std::ptr::null(), Rc::as_ptr)
+    }
+
+    #[test]
+    fn push_mut_and_pop_mut() {
+        let mut list = List::new();
+        assert_eq!(list.pop_mut(), None);
+
+        list.push_mut(1);
+        list.push_mut(2);
+        list.push_mut(3);
+        assert_eq!(list, List::new().append(1).append(2).append(3));
+
+        // a version sharing the nodes keeps them while this list pops through them.
+        let shared = list.tail();
+        assert_eq!(list.pop_mut(), Some(3));
+        assert_eq!(list.pop_mut(), Some(2));
+        assert_eq!(list.pop_mut(), Some(1));
+        assert_eq!(list.pop_mut(), None);
+        assert_eq!(format!("{:?}", shared), "[2, 1]");
+
+        // popping a unique node hands back the elem itself, not a clone.
+        let mut list = List::new();
+        list.push_mut(Rc::new(5));
+        let elem = list.head().unwrap().clone();
+        assert_eq!(Rc::strong_count(&elem), 2);
+        let popped = list.pop_mut().unwrap();
+        assert!(Rc::ptr_eq(&popped, &elem));
+        assert_eq!(Rc::strong_count(&elem), 2);
+    }
+
+    #[test]
+    fn iter_mut_cow_unique() {
+        let mut list: List<_> = (1..=3).collect();
+        let before = head_ptr(&list);
+
+        for elem in list.iter_mut_cow() {
+            *elem *= 10;
+        }
+
+        assert_eq!(format!("{:?}", list), "[10, 20, 30]");
+        assert_eq!(head_ptr(&list), before);
+    }
+
+    #[test]
+    fn iter_mut_cow_shared() {
+        let original: List<_> = (1..=4).collect();
+        let mut list = original.tail().append(0);
+        let unique = head_ptr(&list);
+
+        // only touch the first two: 0 is ours, 2 is shared and has to be copied.
+        for elem in list.iter_mut_cow().take(2) {
+            *elem += 100;
+        }
+
+        assert_eq!(format!("{:?}", list), "[100, 102, 3, 4]");
+        assert_eq!(format!("{:?}", original), "[1, 2, 3, 4]");
+        assert_eq!(head_ptr(&list), unique);
+
+        // the part the iterator never reached is still shared with the original.
+        let rest = list.tail().tail();
+        let original_rest = original.tail().tail();
+        assert_eq!(head_ptr(&rest), head_ptr(&original_rest));
+        assert_ne!(head_ptr(&list.tail()), head_ptr(&original.tail()));
+    }
+
+    #[test]
+    fn map_and_zip() {
+        let list: List<_> = (1..=3).collect();
+        let words: List<_> = vec!["a", "b", "c", "d"].into_iter().collect();
+
+        assert_eq!(format!("{:?}", list.map(|x| x * 2)), "[2, 4, 6]");
+        assert_eq!(
+            format!("{:?}", list.zip(&words)),
+            r#"[(1, "a"), (2, "b"), (3, "c")]"#
+        );
+        assert_eq!(list.zip(&List::<i32>::new()), List::new());
+    }
+
+    #[test]
+    fn filter() {
+        let list: List<_> = (1..=6).collect();
+
+        let odd = list.filter(|x| x % 2 == 1);
+        assert_eq!(format!("{:?}", odd), "[1, 3, 5]");
+
+        // everything after the last rejected element (3) is the original nodes.
+        let big = list.filter(|&x| x != 1 && x != 3);
+        assert_eq!(format!("{:?}", big), "[2, 4, 5, 6]");
+        assert_eq!(head_ptr(&big.drop(1)), head_ptr(&list.drop(3)));
+        assert_ne!(head_ptr(&big), head_ptr(&list.drop(1)));
+
+        // keeping everything copies nothing.
+        assert_eq!(head_ptr(&list.filter(|_| true)), head_ptr(&list));
+        assert_eq!(list.filter(|_| false), List::new());
+    }
+
+    #[test]
+    fn reverse() {
+        let list: List<_> = (1..=3).collect();
+        assert_eq!(format!("{:?}", list.reverse()), "[3, 2, 1]");
+        assert_eq!(list.reverse().reverse(), list);
+        assert_eq!(List::<i32>::new().reverse(), List::new());
+    }
+
+    #[test]
+    fn concat() {
+        let left: List<_> = (1..=2).collect();
+        let right: List<_> = (3..=5).collect();
+
+        let both = left.concat(&right);
+        assert_eq!(format!("{:?}", both), "[1, 2, 3, 4, 5]");
+
+        // the right side is shared, the left side copied.
+        assert_eq!(head_ptr(&both.drop(2)), head_ptr(&right));
+        assert_ne!(head_ptr(&both), head_ptr(&left));
+        assert_eq!(head_ptr(&List::new().concat(&right)), head_ptr(&right));
+    }
+
+    #[test]
+    fn take_and_drop() {
+        let list: List<_> = (1..=4).collect();
+
+        assert_eq!(format!("{:?}", list.take(2)), "[1, 2]");
+        assert_eq!(list.take(0), List::new());
+        assert_eq!(head_ptr(&list.take(4)), head_ptr(&list));
+        assert_eq!(head_ptr(&list.take(10)), head_ptr(&list));
+
+        assert_eq!(format!("{:?}", list.drop(2)), "[3, 4]");
+        assert_eq!(head_ptr(&list.drop(0)), head_ptr(&list));
+        assert_eq!(head_ptr(&list.drop(2)), head_ptr(&list.tail().tail()));
+        assert_eq!(list.drop(10), List::new());
+    }
+
+    #[test]
+    fn folds() {
+        let list: List<_> = (1..=4).collect();
+
+        assert_eq!(list.fold_left(0, |acc, x| acc * 10 + x), 1234);
+        assert_eq!(list.fold_right(0, |x, acc| acc * 10 + x), 4321);
+
+        // fold_right with append rebuilds the list in the same order.
+        let rebuilt = list.fold_right(List::new(), |x, acc| acc.append(*x));
+        assert_eq!(rebuilt, list);
+
+        // recursing once per node would overflow the stack here.
+        let long: List<u64> = (0..1_000_000).collect();
+        assert_eq!(long.fold_right(0, |x, acc| acc + x), 499_999_500_000);
+    }
+
+    #[test]
+    fn ptr_eq() {
+        let list: List<_> = (1..=3).collect();
+        let copy: List<_> = (1..=3).collect();
+
+        assert!(list.ptr_eq(&list.clone()));
+        assert!(!list.ptr_eq(&copy));
+        assert!(list.tail().ptr_eq(&list.drop(1)));
+        assert!(List::<i32>::new().ptr_eq(&list.drop(3)));
+        assert!(!list.ptr_eq(&List::new()));
+    }
+
+    #[test]
+    fn shares_tail_with() {
+        let base: List<_> = (1..=3).collect();
+        let a = base.append(4).append(5);
+        let b = base.tail().append(6);
+
+        assert_eq!(a.shares_tail_with(&b), Some(2));
+        assert_eq!(b.shares_tail_with(&a), Some(2));
+        assert_eq!(a.shares_tail_with(&base), Some(3));
+        assert_eq!(a.shares_tail_with(&a), Some(5));
+
+        // equal elements in different nodes don't count.
+        let copy: List<_> = (1..=3).collect();
+        assert_eq!(a.shares_tail_with(&copy), None);
+        assert_eq!(a.shares_tail_with(&List::new()), None);
+    }
+
+    #[test]
+    fn strong_counts() {
+        let base: List<_> = (1..=3).collect();
+        let mut list = base.append(0);
+
+        assert_eq!(list.strong_count_at(0), Some(1));
+        assert_eq!(list.strong_count_at(1), Some(2));
+        assert_eq!(list.strong_count_at(2), Some(1));
+        assert_eq!(list.strong_count_at(4), None);
+        assert_eq!(list.unique_prefix_len(), 1);
+
+        // once the other list lets go, every node is ours.
+        drop(base);
+        assert_eq!(list.unique_prefix_len(), 4);
+
+        // check the copy-on-write methods do what they promise.
+        let snapshot = list.clone();
+        assert_eq!(list.unique_prefix_len(), 0);
+        list.iter_mut_cow().for_each(|elem| *elem += 1);
+        assert_eq!(list.unique_prefix_len(), 4);
+        assert_eq!(list.shares_tail_with(&snapshot), None);
+    }
+
+    #[test]
+    fn for_loops() {
+        let list = List::new().append(1).append(2).append(3);
+
+        let mut seen = Vec::new();
+        for elem in &list {
+            seen.push(*elem);
+        }
+        assert_eq!(seen, vec![3, 2, 1]);
+    }
 }
@@ -0,0 +1,193 @@
+// hash-consing for third::List: an Interner hands out at most one node per (elem, tail) pair, so
+// lists built through it never hold two copies of the same suffix.
+//
+// lists built through the same interner are equal exactly when they are the same nodes, so
+// List::ptr_eq compares them in O(1). the interner looks tails up by address, which is what makes
+// that work: equal tails are already the same node by the time anything is appended to them. a
+// list built some other way can still be appended to, it just doesn't get deduplicated against.
+//
+// the interner only holds Weak pointers, so it never keeps a list alive by itself, and dropping a
+// list still works the way third::List's Drop expects: Rc::try_unwrap ignores weak references.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::ptr;
+use std::rc::{Rc, Weak};
+
+use super::{List, Node};
+
+pub struct Interner<T> {
+    // nodes by the hash of (elem, tail address). a bucket only holds more than one node when two
+    // pairs hash the same, and may hold nodes that have since been dropped.
+    buckets: HashMap<u64, Vec<Weak<Node<T>>>>,
+    hasher: RandomState,
+    entries: usize,
+    sweep_at: usize, // once entries gets here, dropped nodes are cleared out of every bucket.
+}
+
+impl<T: Eq + Hash> Interner<T> {
+    pub fn new() -> Self {
+        Interner {
+            buckets: HashMap::new(),
+            hasher: RandomState::new(),
+            entries: 0,
+            sweep_at: 64,
+        }
+    }
+
+    // like list.append(elem), but if the interner already has a node for elem in front of this
+    // exact tail that node is returned instead of a new one.
+    pub fn append(&mut self, list: &List<T>, elem: T) -> List<T> {
+        let tail = list.head.as_ref().map_or(ptr::null(), Rc::as_ptr);
+        let hash = self.hasher.hash_one((&elem, tail));
+        let bucket = self.buckets.entry(hash).or_default();
+
+        for node in bucket.iter().filter_map(Weak::upgrade) {
+            let same_tail = node.next.as_ref().map_or(ptr::null(), Rc::as_ptr) == tail;
+            if same_tail && node.elem == elem {
+                return List { head: Some(node) };
+            }
+        }
+
+        let before = bucket.len();
+        bucket.retain(|node| node.strong_count() > 0);
+
+        let node = Rc::new(Node {
+            elem,
+            next: list.head.clone(),
+        });
+        bucket.push(Rc::downgrade(&node));
+        self.entries = self.entries + bucket.len() - before;
+
+        if self.entries >= self.sweep_at {
+            self.sweep();
+        }
+
+        List { head: Some(node) }
+    }
+
+    // builds a list of the items in order, interning every node.
+    pub fn collect<I: IntoIterator<Item = T>>(&mut self, iter: I) -> List<T> {
+        let elems: Vec<T> = iter.into_iter().collect();
+        elems
+            .into_iter()
+            .rev()
+            .fold(List::new(), |list, elem| self.append(&list, elem))
+    }
+
+    // how many interned nodes are still alive.
+    pub fn len(&self) -> usize {
+        self.buckets
+            .values()
+            .flatten()
+            .filter(|node| node.strong_count() > 0)
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // forgets dropped nodes. the next sweep waits until the table has doubled again, so sweeping
+    // costs O(1) amortized per append.
+    fn sweep(&mut self) {
+        self.buckets.retain(|_, bucket| {
+            bucket.retain(|node| node.strong_count() > 0);
+            !bucket.is_empty()
+        });
+
+        self.entries = self.buckets.values().map(Vec::len).sum();
+        self.sweep_at = (self.entries * 2).max(64);
+    }
+}
+
+impl<T: Eq + Hash> Default for Interner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Interner;
+    use crate::third::List;
+
+    #[test]
+    fn append() {
+        let mut interner = Interner::new();
+        let empty = List::new();
+
+        let a = interner.append(&empty, 1);
+        let b = interner.append(&empty, 1);
+        assert!(a.ptr_eq(&b));
+
+        // a different elem or a different tail is a different node.
+        assert!(!interner.append(&empty, 2).ptr_eq(&a));
+        assert!(!interner.append(&a, 1).ptr_eq(&a));
+
+        // equal elements on a tail that wasn't interned aren't deduplicated against it.
+        let outside = List::new().append(1);
+        assert!(!interner.append(&outside, 5).ptr_eq(&interner.append(&a, 5)));
+    }
+
+    #[test]
+    fn equal_lists_are_the_same_nodes() {
+        let mut interner = Interner::new();
+
+        let a = interner.collect(vec![1, 2, 3, 4]);
+        let b = interner.collect(vec![1, 2, 3, 4]);
+        let c = interner.collect(vec![9, 2, 3, 4]);
+
+        assert!(a.ptr_eq(&b));
+        assert!(!a.ptr_eq(&c));
+        assert_eq!(a.shares_tail_with(&c), Some(3));
+        assert_eq!(format!("{:?}", a), "[1, 2, 3, 4]");
+        assert_eq!(interner.len(), 5);
+    }
+
+    #[test]
+    fn prefixes_share_tails() {
+        let mut interner = Interner::new();
+        let tail: Vec<u32> = (0..100).collect();
+
+        let lists: Vec<_> = (1000..1010)
+            .map(|head| interner.collect(std::iter::once(head).chain(tail.iter().copied())))
+            .collect();
+
+        // 100 shared nodes plus one head for each list.
+        assert_eq!(interner.len(), 110);
+        assert_eq!(lists[0].shares_tail_with(&lists[9]), Some(100));
+    }
+
+    #[test]
+    fn forgets_dropped_nodes() {
+        let mut interner = Interner::new();
+
+        let kept = interner.collect(0..10);
+        for i in 0..1_000 {
+            // each of these dies straight away, leaving a dead entry behind.
+            interner.collect(vec![i + 100]);
+        }
+
+        assert_eq!(interner.len(), 10);
+        assert!(interner.entries < 200, "dead entries were never swept");
+
+        // kept is still found, and a dropped pair gets a fresh node.
+        assert!(interner.collect(0..10).ptr_eq(&kept));
+        drop(kept);
+        assert!(interner.is_empty());
+        assert_eq!(format!("{:?}", interner.collect(0..3)), "[0, 1, 2]");
+    }
+
+    #[test]
+    fn long_list() {
+        // interned lists still drop in a loop, the weak references don't stop try_unwrap.
+        let len = if cfg!(miri) { 1_000 } else { 1_000_000 };
+        let mut interner = Interner::new();
+        let list = interner.collect(0..len);
+        assert_eq!(list.iter().count(), len as usize);
+        drop(list);
+        assert!(interner.is_empty());
+    }
+}
@@ -0,0 +1,274 @@
+// the same persistent list as third::List, with Arc in place of Rc so it is Send + Sync.
+//
+// a snapshot can be handed to as many reader threads as needed; appending or taking the tail on
+// any thread makes a new list that shares nodes with the old one, exactly as in the diagram at the
+// top of third.rs. the price is that every clone and drop of a node pointer is an atomic operation.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::sync::Arc;
+
+use crate::traits::PersistentStack;
+
+pub struct List<T> {
+    head: Link<T>,
+}
+
+type Link<T> = Option<Arc<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: None }
+    }
+
+    // returns a new list with elem in front of this one.
+    pub fn append(&self, elem: T) -> List<T> {
+        List {
+            head: Some(Arc::new(Node {
+                elem,
+                next: self.head.clone(),
+            })),
+        }
+    }
+
+    // returns the list with the first element removed.
+    pub fn tail(&self) -> List<T> {
+        List {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    // gets the head of the linked list.
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+
+        // the same loop as third::List, but with Arc::into_inner instead of try_unwrap.
+        //
+        // with threads, try_unwrap can fail in two threads at once when each holds one of the
+        // last two references to a node: both would then drop their Arc, and whichever went last
+        // would drop the whole rest of the list recursively. into_inner does the check and the
+        // decrement as one step, so exactly one of them gets the node back and carries on.
+        while let Some(node) = head {
+            match Arc::into_inner(node) {
+                Some(mut node) => head = node.next.take(),
+                None => break,
+            }
+        }
+    }
+}
+
+// cloning is another handle on the same nodes, O(1) and without T: Clone.
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut len = 0;
+        for elem in self.iter() {
+            elem.hash(state);
+            len += 1;
+        }
+        state.write_usize(len);
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut head = None;
+        let mut tail = &mut head;
+
+        // a freshly made Arc is unique, so Arc::get_mut always succeeds here.
+        for elem in iter {
+            let node = tail.insert(Arc::new(Node { elem, next: None }));
+            tail = &mut Arc::get_mut(node).unwrap().next;
+        }
+
+        List { head }
+    }
+}
+
+// copies the existing nodes, which may be shared, and adds the items after them.
+impl<T: Clone> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        *self = self.iter().cloned().chain(iter).collect();
+    }
+}
+
+impl<T> PersistentStack<T> for List<T> {
+    fn push(&self, elem: T) -> Self {
+        self.append(elem)
+    }
+
+    fn tail(&self) -> Self {
+        List::tail(self)
+    }
+
+    fn head(&self) -> Option<&T> {
+        List::head(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use crate::{conformance, differential};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn conformance() {
+        conformance::persistent_stack::<List<i32>>();
+    }
+
+    #[test]
+    fn differential() {
+        for seed in 0..16 {
+            differential::persistent_stack::<List<i32>>(seed, 2_000);
+        }
+    }
+
+    #[test]
+    fn send_and_sync() {
+        fn assert_send_sync<S: Send + Sync>() {}
+
+        assert_send_sync::<List<i32>>();
+        assert_send_sync::<List<String>>();
+    }
+
+    #[test]
+    fn snapshots_across_threads() {
+        let base: List<_> = (0..100).collect();
+
+        // every reader gets its own version built on the shared base.
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let base = base.clone();
+                thread::spawn(move || {
+                    let mine = base.tail().append(1000 + i);
+                    assert_eq!(mine.head(), Some(&(1000 + i)));
+                    assert_eq!(mine.iter().count(), 100);
+                    mine
+                })
+            })
+            .collect();
+
+        let versions: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        // the base is untouched and the versions still share its nodes.
+        assert_eq!(
+            base.iter().copied().collect::<Vec<_>>(),
+            (0..100).collect::<Vec<_>>()
+        );
+        for (i, version) in versions.iter().enumerate() {
+            assert_eq!(version.head(), Some(&(1000 + i as i32)));
+            assert_eq!(version.tail(), base.tail());
+        }
+    }
+
+    #[test]
+    fn concurrent_drop() {
+        // threads drop lists sharing one long tail at the same time. whichever drops last has to
+        // free the tail in the loop; recursing would overflow a thread's stack.
+        for _ in 0..4 {
+            let shared: List<u32> = (0..200_000).collect();
+            let lists: Vec<_> = (0..4).map(|i| shared.append(i)).collect();
+            drop(shared);
+
+            let barrier = Arc::new(std::sync::Barrier::new(lists.len()));
+            let handles: Vec<_> = lists
+                .into_iter()
+                .map(|list| {
+                    let barrier = barrier.clone();
+                    thread::spawn(move || {
+                        barrier.wait();
+                        drop(list);
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+// common names for the operations every list in this crate already has, so code can be written
+// once against a trait and the backing list swapped without touching the call sites.
+//
+//                       | Stack | Queue | Deque | PersistentStack
+//   first::List         |   x   |       |       |
+//   second::List        |   x   |       |       |
+//   third::List         |       |       |       |        x
+//   third::sync::List   |       |       |       |        x
+//   fourth::List        |   x   |   x   |   x   |
+//   fifth::List         |       |   x   |       |
+//   random_access::List |       |       |       |        x
+//   stream::Stream      |       |       |       |        x
+//
+// the queue methods are named push_back/pop_front rather than push/pop so that a list can be both
+// a Stack and a Queue (like fourth::List) without the calls becoming ambiguous.
+
+use std::ops::Deref;
+
+// last in, first out.
+pub trait Stack<T> {
+    // what peek hands out: &T for the Box based lists, Ref<T> for the RefCell based one.
+    type Peek<'a>: Deref<Target = T>
+    where
+        Self: 'a;
+
+    fn push(&mut self, elem: T);
+
+    fn pop(&mut self) -> Option<T>;
+
+    // the element pop would return next.
+    fn peek(&self) -> Option<Self::Peek<'_>>;
+
+    fn is_empty(&self) -> bool {
+        self.peek().is_none()
+    }
+}
+
+// first in, first out.
+pub trait Queue<T> {
+    type Peek<'a>: Deref<Target = T>
+    where
+        Self: 'a;
+
+    fn push_back(&mut self, elem: T);
+
+    fn pop_front(&mut self) -> Option<T>;
+
+    // the element pop_front would return next.
+    fn peek_front(&self) -> Option<Self::Peek<'_>>;
+
+    fn is_empty(&self) -> bool {
+        self.peek_front().is_none()
+    }
+}
+
+// a queue that can also be pushed and popped at the other end.
+pub trait Deque<T>: Queue<T> {
+    fn push_front(&mut self, elem: T);
+
+    fn pop_back(&mut self) -> Option<T>;
+
+    fn peek_back(&self) -> Option<Self::Peek<'_>>;
+}
+
+// a stack that never changes: push and tail return new versions that share nodes with the old one.
+pub trait PersistentStack<T>: Sized {
+    fn push(&self, elem: T) -> Self;
+
+    // everything but the head, or the empty stack if there is no head.
+    fn tail(&self) -> Self;
+
+    fn head(&self) -> Option<&T>;
+
+    fn is_empty(&self) -> bool {
+        self.head().is_none()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Deque, PersistentStack, Queue, Stack};
+    use crate::{fifth, first, fourth, second, third};
+
+    // generic code only names the trait, so any of the lists can be passed in.
+    fn reverse_with<S: Stack<i32> + Default>(elems: &[i32]) -> Vec<i32> {
+        let mut stack = S::default();
+        for elem in elems {
+            stack.push(*elem);
+        }
+
+        let mut reversed = Vec::new();
+        while let Some(elem) = stack.pop() {
+            reversed.push(elem);
+        }
+        reversed
+    }
+
+    fn round_trip_with<Q: Queue<i32> + Default>(elems: &[i32]) -> Vec<i32> {
+        let mut queue = Q::default();
+        for elem in elems {
+            queue.push_back(*elem);
+        }
+
+        let mut out = Vec::new();
+        while let Some(elem) = queue.pop_front() {
+            out.push(elem);
+        }
+        out
+    }
+
+    #[test]
+    fn stacks() {
+        let elems = [1, 2, 3];
+
+        assert_eq!(reverse_with::<first::List<_>>(&elems), vec![3, 2, 1]);
+        assert_eq!(reverse_with::<second::List<_>>(&elems), vec![3, 2, 1]);
+        assert_eq!(reverse_with::<fourth::List<_>>(&elems), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn queues() {
+        let elems = [1, 2, 3];
+
+        assert_eq!(round_trip_with::<fourth::List<_>>(&elems), vec![1, 2, 3]);
+        assert_eq!(round_trip_with::<fifth::List<_>>(&elems), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn deque() {
+        let mut deque = fourth::List::new();
+        Deque::push_front(&mut deque, 2);
+        Deque::push_front(&mut deque, 1);
+        Queue::push_back(&mut deque, 3);
+
+        assert_eq!(Deque::peek_back(&deque).as_deref(), Some(&3));
+        assert_eq!(Queue::peek_front(&deque).as_deref(), Some(&1));
+        assert_eq!(Deque::pop_back(&mut deque), Some(3));
+        assert_eq!(Queue::pop_front(&mut deque), Some(1));
+        assert_eq!(Deque::pop_back(&mut deque), Some(2));
+        assert!(Queue::is_empty(&deque));
+    }
+
+    #[test]
+    fn persistent_stack() {
+        fn build<S: PersistentStack<i32>>(empty: S) -> S {
+            empty.push(1).push(2).push(3)
+        }
+
+        let list = build(third::List::new());
+        assert_eq!(PersistentStack::head(&list), Some(&3));
+
+        let tail = PersistentStack::tail(&list);
+        assert_eq!(PersistentStack::head(&tail), Some(&2));
+
+        // check the original version is untouched.
+        assert_eq!(PersistentStack::head(&list), Some(&3));
+        assert!(PersistentStack::is_empty(&third::List::<i32>::new()));
+    }
+}
@@ -0,0 +1,178 @@
+// a cursor into a third::List that edits persistently: every move or edit returns a new zipper
+// and leaves the old one as it was, sharing almost everything with it.
+//
+// the list is split at the focus. the elements before it are kept in a list of their own, nearest
+// first, so that both neighbours of the focus are at the head of a list:
+//
+//   list   = 1, 2, 3, 4, 5   (focus on 3)
+//   left  -> 2 -> 1
+//   focus    3
+//   right -> 4 -> 5
+//
+// moving the focus is then a tail on one side and an append on the other, and editing at the focus
+// only replaces the focus, so every operation is O(1). only rebuilding the whole list with to_list
+// has to walk the left side.
+//
+// third::List nodes can't give up their elements, so an element moved between the sides is cloned.
+
+use crate::third::List;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Zipper<T> {
+    left: List<T>, // nearest first.
+    focus: T,
+    right: List<T>,
+}
+
+impl<T: Clone> Zipper<T> {
+    // a zipper focused on the head of list, or None if there is nothing to focus on.
+    pub fn from_list(list: &List<T>) -> Option<Zipper<T>> {
+        list.head().map(|head| Zipper {
+            left: List::new(),
+            focus: head.clone(),
+            right: list.tail(),
+        })
+    }
+
+    pub fn focus(&self) -> &T {
+        &self.focus
+    }
+
+    // the zipper focused one element to the left, or None if the focus is the first element.
+    pub fn left(&self) -> Option<Zipper<T>> {
+        self.left.head().map(|elem| Zipper {
+            left: self.left.tail(),
+            focus: elem.clone(),
+            right: self.right.append(self.focus.clone()),
+        })
+    }
+
+    // the zipper focused one element to the right, or None if the focus is the last element.
+    pub fn right(&self) -> Option<Zipper<T>> {
+        self.right.head().map(|elem| Zipper {
+            left: self.left.append(self.focus.clone()),
+            focus: elem.clone(),
+            right: self.right.tail(),
+        })
+    }
+
+    // the same position with the focus swapped for elem.
+    pub fn replace(&self, elem: T) -> Zipper<T> {
+        Zipper {
+            left: self.left.clone(),
+            focus: elem,
+            right: self.right.clone(),
+        }
+    }
+
+    // puts elem in at the focus, pushing the old focus one place to the right.
+    pub fn insert(&self, elem: T) -> Zipper<T> {
+        Zipper {
+            left: self.left.clone(),
+            focus: elem,
+            right: self.right.append(self.focus.clone()),
+        }
+    }
+
+    // removes the focus. the element after it becomes the focus, or the one before it if it was
+    // the last. None if it was the only element.
+    pub fn delete(&self) -> Option<Zipper<T>> {
+        if let Some(elem) = self.right.head() {
+            return Some(Zipper {
+                left: self.left.clone(),
+                focus: elem.clone(),
+                right: self.right.tail(),
+            });
+        }
+
+        self.left.head().map(|elem| Zipper {
+            left: self.left.tail(),
+            focus: elem.clone(),
+            right: List::new(),
+        })
+    }
+
+    // the whole list again, in order. the right side is shared, the focus and the left side are
+    // appended on to it, so this is O(n) in the number of elements before the focus.
+    pub fn to_list(&self) -> List<T> {
+        self.left
+            .iter()
+            .fold(self.right.append(self.focus.clone()), |list, elem| {
+                list.append(elem.clone())
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Zipper;
+    use crate::third::List;
+
+    fn list(elems: &[i32]) -> List<i32> {
+        elems.iter().copied().collect()
+    }
+
+    #[test]
+    fn moving() {
+        // check empty list behaves right.
+        assert_eq!(Zipper::from_list(&List::<i32>::new()), None);
+
+        let zipper = Zipper::from_list(&list(&[1, 2, 3])).unwrap();
+        assert_eq!(zipper.focus(), &1);
+        assert_eq!(zipper.left(), None);
+
+        let zipper = zipper.right().unwrap().right().unwrap();
+        assert_eq!(zipper.focus(), &3);
+        assert_eq!(zipper.right(), None);
+
+        let zipper = zipper.left().unwrap();
+        assert_eq!(zipper.focus(), &2);
+        assert_eq!(zipper.to_list(), list(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn editing() {
+        let start = Zipper::from_list(&list(&[1, 2, 3]))
+            .unwrap()
+            .right()
+            .unwrap();
+
+        let replaced = start.replace(20);
+        assert_eq!(replaced.to_list(), list(&[1, 20, 3]));
+
+        let inserted = start.insert(10);
+        assert_eq!(inserted.focus(), &10);
+        assert_eq!(inserted.to_list(), list(&[1, 10, 2, 3]));
+
+        let deleted = start.delete().unwrap();
+        assert_eq!(deleted.focus(), &3);
+        assert_eq!(deleted.to_list(), list(&[1, 3]));
+
+        // deleting the last element moves the focus left, deleting the only one leaves nothing.
+        let deleted = deleted.delete().unwrap();
+        assert_eq!(deleted.focus(), &1);
+        assert_eq!(deleted.to_list(), list(&[1]));
+        assert_eq!(deleted.delete(), None);
+
+        // every version is still there.
+        assert_eq!(start.to_list(), list(&[1, 2, 3]));
+        assert_eq!(replaced.to_list(), list(&[1, 20, 3]));
+    }
+
+    #[test]
+    fn shares_structure() {
+        let original = list(&[1, 2, 3, 4, 5]);
+        let zipper = Zipper::from_list(&original).unwrap();
+        assert!(zipper.right.ptr_eq(&original.tail()));
+
+        let moved = zipper.right().unwrap();
+        assert!(moved.right.ptr_eq(&original.drop(2)));
+
+        let edited = moved.replace(20).insert(10);
+        assert_eq!(edited.right.shares_tail_with(&original), Some(3));
+        assert!(edited.left.ptr_eq(&moved.left));
+
+        // rebuilding shares everything right of the focus.
+        assert_eq!(edited.to_list().shares_tail_with(&original), Some(3));
+    }
+}